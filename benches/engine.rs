@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use uno::{Position, Ruleset};
+
+fn action_generation(c: &mut Criterion) {
+    let mut position = Position::dealt(4, Ruleset::default(), 1);
+
+    c.bench_function("legal actions", |b| {
+        b.iter(|| position.legal_action_count())
+    });
+}
+
+fn action_execution(c: &mut Criterion) {
+    let position = Position::dealt(4, Ruleset::default(), 1);
+
+    c.bench_function("play out a game", |b| {
+        b.iter_batched(
+            || position.clone(),
+            |mut position| position.play_out(),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("perft 4", |b| b.iter(|| position.perft(4)));
+}
+
+criterion_group!(benches, action_generation, action_execution);
+criterion_main!(benches);
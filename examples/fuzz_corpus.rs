@@ -0,0 +1,23 @@
+//! Records games between the heuristic bots as the seed corpus of the `actions` fuzz target:
+//!
+//!     cargo run --example fuzz_corpus --features fuzzing
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const GAMES: u64 = 32;
+
+fn main() -> io::Result<()> {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/actions");
+    fs::create_dir_all(&corpus)?;
+
+    for seed in 0..GAMES {
+        fs::write(
+            corpus.join(format!("replay-{:02}", seed)),
+            uno::fuzzing::record_game(seed),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! A game played through nothing but the library's public API, the way another crate would
+//! embed the engine. Every seat plays the first legal action it is offered, preferring
+//! numeric cards, and shows what the current player sees at the end, along with which of the
+//! cards left in each hand the rules would let be played on the final top card.
+//!
+//!     cargo run --example library -- 4 42
+
+use std::env;
+
+use uno::{Action, Card, Event, GameRuntime, PlayerView, Ruleset};
+
+fn choose(legal_actions: &[Action]) -> Action {
+    legal_actions
+        .iter()
+        .copied()
+        .find(|action| {
+            matches!(
+                action,
+                Action::Play {
+                    card: Card::Normal(_)
+                }
+            )
+        })
+        .unwrap_or(legal_actions[0])
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let num_players = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(3);
+    let seed = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(0);
+
+    let mut game_runtime = GameRuntime::from_seed(num_players, Ruleset::default(), seed);
+    let mut legal_actions = vec![];
+
+    while game_runtime.winner().is_none() {
+        game_runtime.legal_actions_into(&mut legal_actions);
+        game_runtime
+            .execute_action(choose(&legal_actions))
+            .expect("legal actions are always accepted");
+    }
+
+    for event in game_runtime.events() {
+        match event {
+            Event::Played { player_idx, card } => {
+                println!("player {} played {:?}", player_idx, card)
+            }
+            Event::Won { player_idx } => println!("player {} won", player_idx),
+            _ => {}
+        }
+    }
+
+    let view = PlayerView::new(&game_runtime, game_runtime.current_player_idx());
+    println!("top card: {:?}", view.table.top_card());
+    println!("hand sizes: {:?}", view.table.hand_sizes);
+
+    let game_state = game_runtime.game_state();
+    for player_idx in 0..game_runtime.num_players() {
+        let playable: Vec<Card> = game_runtime
+            .hand(player_idx)
+            .iter()
+            .copied()
+            .filter(|&card| game_state.can_play_card(card))
+            .collect();
+        println!("player {} could play {:?}", player_idx, playable);
+    }
+}
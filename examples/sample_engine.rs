@@ -0,0 +1,81 @@
+//! A reference engine for the bot protocol, written against nothing but the standard library so
+//! it reads the same as an engine in any other language would.
+//!
+//! It plays its highest numeric card, keeps wild cards for when nothing else fits, names the
+//! color it holds the most of and never calls a bluff. Try it against the built-in bots with:
+//!
+//!     cargo build --example sample_engine
+//!     cargo run --bin simulate -- --bots greedy,engine:target/debug/examples/sample_engine
+
+use std::io::{self, BufRead, Write};
+
+const COLORS: [&str; 4] = ["blue", "green", "yellow", "red"];
+
+/// Cards are a color initial and a value (`r5`), or `w` and `w4` for the wild cards.
+fn is_wild(card: &str) -> bool {
+    card.starts_with('w')
+}
+
+fn numeric_value(card: &str) -> Option<u32> {
+    if is_wild(card) {
+        return None;
+    }
+    card[1..].parse().ok()
+}
+
+fn most_held_color(hand: &[String]) -> &'static str {
+    COLORS
+        .into_iter()
+        .rev()
+        .max_by_key(|color| {
+            hand.iter()
+                .filter(|card| !is_wild(card) && color.starts_with(&card[..1]))
+                .count()
+        })
+        .unwrap()
+}
+
+fn choose<'a>(legal_actions: &[&'a str], hand: &[String]) -> &'a str {
+    let best_numeric = legal_actions
+        .iter()
+        .filter_map(|&action| numeric_value(action).map(|value| (value, action)))
+        .max();
+    if let Some((_, action)) = best_numeric {
+        return action;
+    }
+
+    let color = most_held_color(hand);
+    let preferences = [color, "w", "w4", "draw1", "draw4", "draw6", "pass"];
+    preferences
+        .into_iter()
+        .find_map(|preferred| legal_actions.iter().find(|&&action| action == preferred))
+        .copied()
+        .unwrap_or(legal_actions[0])
+}
+
+fn main() -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut hand: Vec<String> = vec![];
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("uno") => writeln!(out, "uno ok")?,
+            Some("hand") => hand = words.map(str::to_owned).collect(),
+            Some("go") => {
+                let legal_actions: Vec<&str> = words.collect();
+                writeln!(out, "move {}", choose(&legal_actions, &hand))?;
+            }
+            Some("quit") => break,
+            // Events, the ruleset and the top card don't matter to this engine.
+            _ => {}
+        }
+
+        out.flush()?;
+    }
+
+    Ok(())
+}
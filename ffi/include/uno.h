@@ -0,0 +1,164 @@
+#ifndef UNO_H
+#define UNO_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+
+
+// Numeric cards are `color * 10 + value`, followed by the two wild cards.
+#define UNO_NUM_CARD_TYPES 42
+
+#define UNO_CARD_WILD 40
+
+#define UNO_CARD_DRAW_FOUR 41
+
+#define UNO_COLOR_BLUE 0
+
+#define UNO_COLOR_GREEN 1
+
+#define UNO_COLOR_YELLOW 2
+
+#define UNO_COLOR_RED 3
+
+// Playing a card is the action with the card's index. Choosing a color adds the color to this.
+#define UNO_ACTION_CHOOSE_COLOR 42
+
+#define UNO_ACTION_DRAW_ONE 46
+
+#define UNO_ACTION_DRAW_FOUR 47
+
+#define UNO_ACTION_CALL_BLUFF 48
+
+#define UNO_ACTION_PASS 49
+
+#define UNO_NUM_ACTIONS 50
+
+// Stands for a missing color, card or winner.
+#define UNO_NONE -1
+
+typedef enum UnoStatus {
+  UNO_STATUS_OK = 0,
+  UNO_STATUS_NULL_POINTER,
+  UNO_STATUS_INVALID_ACTION,
+  UNO_STATUS_ILLEGAL_ACTION,
+  UNO_STATUS_GAME_IS_OVER,
+} UnoStatus;
+
+typedef enum UnoDirection {
+  UNO_DIRECTION_FORWARD = 0,
+  UNO_DIRECTION_BACKWARD,
+} UnoDirection;
+
+// A game in progress. Only ever handled through a pointer.
+typedef struct UnoGame UnoGame;
+
+// A snapshot of what one player can see. It owns its arrays, so it outlives the game it was
+// taken from, and has to be released with `uno_player_view_free`.
+typedef struct UnoPlayerView {
+  size_t player_idx;
+  size_t current_player_idx;
+  size_t num_players;
+  // Card indices, `hand_len` of them.
+  const uint8_t *hand;
+  size_t hand_len;
+  // How many cards each player holds, `num_players` of them.
+  const size_t *hand_sizes;
+  uint32_t top_card;
+  // `UNO_NONE` while a wild card waits for its color.
+  int32_t active_color;
+  // The card this player just drew, or `UNO_NONE`.
+  int32_t drawn_card;
+  size_t drawing_deck_len;
+  size_t discard_pile_len;
+  enum UnoDirection direction;
+  // `UNO_NONE` until somebody wins.
+  int32_t winner;
+} UnoPlayerView;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Deals a game between `num_players` players, shuffled by `seed`. `ruleset` is one of the
+// ruleset names, such as `"standard"` or `"quick"`, or null for the standard rules.
+//
+// Returns null if the ruleset is unknown or the players can't be dealt a hand. The game has to
+// be released with `uno_game_free`.
+//
+// # Safety
+//
+// `ruleset` must be null or point to a NUL-terminated string.
+struct UnoGame *uno_game_new(size_t num_players, const char *ruleset, uint64_t seed);
+
+// Copies a game, e.g. to try actions out on. Returns null if `game` is null.
+//
+// # Safety
+//
+// `game` must be null or a live game from `uno_game_new` or `uno_game_clone`.
+struct UnoGame *uno_game_clone(const struct UnoGame *game);
+
+// # Safety
+//
+// `game` must be null or a live game, which is no longer usable afterwards.
+void uno_game_free(struct UnoGame *game);
+
+// # Safety
+//
+// `game` must be null or a live game.
+size_t uno_game_num_players(const struct UnoGame *game);
+
+// # Safety
+//
+// `game` must be null or a live game.
+size_t uno_game_current_player(const struct UnoGame *game);
+
+// The winner's seat, or `UNO_NONE` while the game goes on.
+//
+// # Safety
+//
+// `game` must be null or a live game.
+int32_t uno_game_winner(const struct UnoGame *game);
+
+// Writes up to `capacity` of the current player's legal actions to `actions` and returns how
+// many there are in total, so a call with a capacity of zero finds out how much room is needed.
+// `UNO_NUM_ACTIONS` is always enough.
+//
+// # Safety
+//
+// `game` must be null or a live game, and `actions` must have room for `capacity` actions.
+size_t uno_game_legal_actions(const struct UnoGame *game, uint32_t *actions, size_t capacity);
+
+// Takes `action` for the current player. The game is left untouched unless this returns
+// `UNO_STATUS_OK`.
+//
+// # Safety
+//
+// `game` must be null or a live game.
+enum UnoStatus uno_game_apply_action(struct UnoGame *game, uint32_t action);
+
+// What `player_idx` can see right now, or null if there is no such player. The view has to be
+// released with `uno_player_view_free`.
+//
+// # Safety
+//
+// `game` must be null or a live game.
+struct UnoPlayerView *uno_game_player_view(const struct UnoGame *game, size_t player_idx);
+
+// # Safety
+//
+// `view` must be null or a view from `uno_game_player_view` that hasn't been freed yet.
+void uno_player_view_free(struct UnoPlayerView *view);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* UNO_H */
@@ -0,0 +1,145 @@
+/* Plays games through the C API, checking what a client written in C relies on. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "uno.h"
+
+#define CHECK(condition)                                                      \
+    do {                                                                      \
+        if (!(condition)) {                                                   \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,  \
+                    #condition);                                              \
+            exit(1);                                                          \
+        }                                                                     \
+    } while (0)
+
+static int contains(const uint32_t *actions, size_t len, uint32_t action) {
+    for (size_t i = 0; i < len; i++) {
+        if (actions[i] == action) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static void check_view(const UnoGame *game, size_t player_idx) {
+    UnoPlayerView *view = uno_game_player_view(game, player_idx);
+    CHECK(view != NULL);
+    CHECK(view->player_idx == player_idx);
+    CHECK(view->num_players == uno_game_num_players(game));
+    CHECK(view->current_player_idx == uno_game_current_player(game));
+    CHECK(view->hand_len == view->hand_sizes[player_idx]);
+    CHECK(view->top_card < UNO_NUM_CARD_TYPES);
+    CHECK(view->winner == uno_game_winner(game));
+
+    size_t total_cards = view->drawing_deck_len + view->discard_pile_len;
+    for (size_t i = 0; i < view->num_players; i++) {
+        total_cards += view->hand_sizes[i];
+    }
+    CHECK(total_cards == 84);
+
+    for (size_t i = 0; i < view->hand_len; i++) {
+        CHECK(view->hand[i] < UNO_NUM_CARD_TYPES);
+    }
+
+    uno_player_view_free(view);
+}
+
+static void test_invalid_games(void) {
+    CHECK(uno_game_new(1, NULL, 0) == NULL);
+    CHECK(uno_game_new(11, "standard", 0) == NULL);
+    CHECK(uno_game_new(2, "calvinball", 0) == NULL);
+
+    UnoGame *game = uno_game_new(2, "quick", 0);
+    CHECK(game != NULL);
+    CHECK(uno_game_player_view(game, 2) == NULL);
+    CHECK(uno_game_apply_action(game, UNO_NUM_ACTIONS) == UNO_STATUS_INVALID_ACTION);
+    CHECK(uno_game_apply_action(NULL, UNO_ACTION_PASS) == UNO_STATUS_NULL_POINTER);
+    uno_game_free(game);
+    uno_game_free(NULL);
+}
+
+static void test_same_seed_same_game(void) {
+    UnoGame *first = uno_game_new(4, NULL, 42);
+    UnoGame *second = uno_game_new(4, "standard", 42);
+    uint32_t first_actions[UNO_NUM_ACTIONS];
+    uint32_t second_actions[UNO_NUM_ACTIONS];
+
+    size_t len = uno_game_legal_actions(first, first_actions, UNO_NUM_ACTIONS);
+    CHECK(len > 0);
+    CHECK(uno_game_legal_actions(second, second_actions, UNO_NUM_ACTIONS) == len);
+    CHECK(memcmp(first_actions, second_actions, len * sizeof(uint32_t)) == 0);
+    CHECK(uno_game_legal_actions(first, NULL, 0) == len);
+
+    uno_game_free(first);
+    uno_game_free(second);
+}
+
+static void test_illegal_actions_change_nothing(void) {
+    UnoGame *game = uno_game_new(3, NULL, 7);
+    uint32_t actions[UNO_NUM_ACTIONS];
+    size_t len = uno_game_legal_actions(game, actions, UNO_NUM_ACTIONS);
+
+    uint32_t illegal = 0;
+    while (contains(actions, len, illegal)) {
+        illegal++;
+    }
+
+    UnoPlayerView *before = uno_game_player_view(game, 0);
+    CHECK(uno_game_apply_action(game, illegal) == UNO_STATUS_ILLEGAL_ACTION);
+    UnoPlayerView *after = uno_game_player_view(game, 0);
+    CHECK(before->hand_len == after->hand_len);
+    CHECK(memcmp(before->hand, after->hand, before->hand_len) == 0);
+    CHECK(before->current_player_idx == after->current_player_idx);
+
+    uno_player_view_free(before);
+    uno_player_view_free(after);
+    uno_game_free(game);
+}
+
+static void test_games_play_out(void) {
+    for (uint64_t seed = 0; seed < 20; seed++) {
+        UnoGame *game = uno_game_new(2 + seed % 5, NULL, seed);
+        uint32_t actions[UNO_NUM_ACTIONS];
+        uint64_t rng = seed;
+        size_t turns = 0;
+
+        while (uno_game_winner(game) == UNO_NONE) {
+            CHECK(turns++ < 10000);
+            check_view(game, uno_game_current_player(game));
+
+            size_t len = uno_game_legal_actions(game, actions, UNO_NUM_ACTIONS);
+            CHECK(len > 0 && len <= UNO_NUM_ACTIONS);
+
+            rng = rng * 6364136223846793005u + 1442695040888963407u;
+            uint32_t action = actions[(rng >> 33) % len];
+
+            UnoGame *copy = uno_game_clone(game);
+            CHECK(uno_game_apply_action(game, action) == UNO_STATUS_OK);
+            CHECK(uno_game_legal_actions(copy, actions, UNO_NUM_ACTIONS) == len);
+            uno_game_free(copy);
+        }
+
+        int32_t winner = uno_game_winner(game);
+        CHECK(winner >= 0 && (size_t)winner < uno_game_num_players(game));
+        CHECK(uno_game_legal_actions(game, actions, UNO_NUM_ACTIONS) == 0);
+        CHECK(uno_game_apply_action(game, UNO_ACTION_PASS) == UNO_STATUS_GAME_IS_OVER);
+
+        UnoPlayerView *view = uno_game_player_view(game, (size_t)winner);
+        CHECK(view->hand_len == 0);
+        CHECK(view->winner == winner);
+        uno_player_view_free(view);
+
+        uno_game_free(game);
+    }
+}
+
+int main(void) {
+    test_invalid_games();
+    test_same_seed_same_game();
+    test_illegal_actions_change_nothing();
+    test_games_play_out();
+    puts("ok");
+    return 0;
+}
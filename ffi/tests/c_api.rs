@@ -0,0 +1,38 @@
+//! Builds the C tests in `tests/c` against the static library and `include/uno.h` with the
+//! system C compiler, then runs them.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn c_tests_pass() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Test binaries live in `deps`, next to which cargo puts the library.
+    let library = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(|deps| deps.parent())
+        .unwrap()
+        .join("libuno_ffi.a");
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_uno");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&binary)
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/test_uno.c"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .expect("a C compiler is needed to run the C tests");
+    assert!(status.success(), "the C tests didn't compile");
+
+    let output = Command::new(&binary).output().unwrap();
+    assert!(
+        output.status.success(),
+        "the C tests failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,32 @@
+//! Regenerates `include/uno.h` from `src/ffi.rs` with cbindgen and checks that the checked-in
+//! header matches, so that it can't go stale. Run with `UPDATE_HEADER=1` to write the new header
+//! instead.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn header_is_up_to_date() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let header_path = manifest_dir.join("include/uno.h");
+    let config = cbindgen::Config::from_file(manifest_dir.join("cbindgen.toml")).unwrap();
+
+    let mut header = vec![];
+    cbindgen::Builder::new()
+        .with_crate(manifest_dir.parent().unwrap())
+        .with_config(config)
+        .generate()
+        .expect("cbindgen couldn't read src/ffi.rs")
+        .write(&mut header);
+
+    if env::var_os("UPDATE_HEADER").is_some() {
+        fs::write(&header_path, header).unwrap();
+        return;
+    }
+
+    assert!(
+        fs::read(&header_path).unwrap() == header,
+        "ffi/include/uno.h is out of date, rerun this test with UPDATE_HEADER=1 to regenerate it"
+    );
+}
@@ -0,0 +1,64 @@
+#![no_std]
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::panic::PanicInfo;
+use core::ptr;
+
+use uno::{Deck, GameRuntime, Ruleset};
+
+const HEAP_SIZE: usize = 1 << 20;
+
+/// Hands out memory from a fixed buffer and never takes it back, like a handheld might.
+struct BumpAllocator {
+    heap: UnsafeCell<[u8; HEAP_SIZE]>,
+    next: UnsafeCell<usize>,
+}
+
+unsafe impl Sync for BumpAllocator {}
+
+unsafe impl GlobalAlloc for BumpAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let heap = self.heap.get() as *mut u8;
+        let start = (heap as usize + *self.next.get()).next_multiple_of(layout.align());
+        let end = start - heap as usize + layout.size();
+        if end > HEAP_SIZE {
+            return ptr::null_mut();
+        }
+
+        *self.next.get() = end;
+        start as *mut u8
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator {
+    heap: UnsafeCell::new([0; HEAP_SIZE]),
+    next: UnsafeCell::new(0),
+};
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
+
+/// Deals a game, takes the first player's first legal action and returns how many actions the
+/// rules give the next player's hand, so that the rules and the runtime end up in the library.
+#[no_mangle]
+pub extern "C" fn uno_no_std_check(seed: u64) -> usize {
+    let mut game_runtime = GameRuntime::from_seed(2, Ruleset::default(), seed);
+    let action = game_runtime.legal_actions()[0];
+
+    match game_runtime.execute_action(action) {
+        Ok(()) => {
+            let hand = game_runtime.hand(game_runtime.current_player_idx());
+            game_runtime
+                .game_state()
+                .get_actions_for_deck(&Deck::new(hand.to_vec()))
+                .len()
+        }
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt;
+use std::io;
+
+use crate::game_executor::{ActionError, Event, GameRuntime};
+use crate::game_state::Action;
+use crate::view::{OmniscientView, PlayerView};
+
+#[derive(Debug)]
+pub enum AgentError {
+    /// The player left the game, e.g. a human quitting or a remote client disconnecting.
+    Left,
+    /// The player hasn't decided yet, e.g. a remote client whose action has yet to come in.
+    Waiting,
+    Io(io::Error),
+    /// An external engine took too long to answer.
+    TimedOut,
+    /// An external engine exited or closed its output.
+    Crashed,
+    /// An external engine answered with something other than one of the legal actions.
+    InvalidReply(String),
+}
+
+impl From<io::Error> for AgentError {
+    fn from(err: io::Error) -> AgentError {
+        AgentError::Io(err)
+    }
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AgentError::Left => write!(f, "player left the game"),
+            AgentError::Waiting => write!(f, "player hasn't decided yet"),
+            AgentError::Io(err) => write!(f, "{}", err),
+            AgentError::TimedOut => write!(f, "engine took too long to answer"),
+            AgentError::Crashed => write!(f, "engine crashed"),
+            AgentError::InvalidReply(reply) => {
+                write!(f, "engine replied {:?}, which isn't a legal action", reply)
+            }
+        }
+    }
+}
+
+/// Someone sitting at the table, be it a human at the terminal, a bot or a remote client.
+pub trait Agent {
+    /// Picks one of `legal_actions`, which is never empty.
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError>;
+
+    /// Called for everything that happens at the table, including this agent's own actions.
+    fn observe_event(&mut self, _event: &Event) {}
+
+    /// Every hand is revealed once the game is over.
+    fn observe_game_over(&mut self, _view: &OmniscientView, _winner: usize) {}
+}
+
+#[derive(Debug)]
+pub enum GameError {
+    Agent {
+        player_idx: usize,
+        error: AgentError,
+    },
+    IllegalAction {
+        player_idx: usize,
+        error: ActionError,
+    },
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Agent { player_idx, error } => {
+                write!(f, "player {}: {}", player_idx + 1, error)
+            }
+            GameError::IllegalAction { player_idx, error } => {
+                write!(f, "player {}: {}", player_idx + 1, error)
+            }
+        }
+    }
+}
+
+/// Asks the current player's agent for an action and plays it, letting every agent know what
+/// happened and, if that won the game, see every hand. Returns the index of the first event the
+/// action caused, or `None` if the agent is still waiting to decide.
+pub(crate) fn play_next_action(
+    game_runtime: &mut GameRuntime,
+    agents: &mut [Box<dyn Agent>],
+) -> Result<Option<usize>, GameError> {
+    assert_eq!(
+        agents.len(),
+        game_runtime.num_players(),
+        "every seat needs an agent"
+    );
+
+    let player_idx = game_runtime.current_player_idx();
+    let legal_actions = game_runtime.legal_actions();
+    let view = PlayerView::new(game_runtime, player_idx);
+
+    let action = match agents[player_idx].choose_action(&view, &legal_actions) {
+        Ok(action) => action,
+        Err(AgentError::Waiting) => return Ok(None),
+        Err(error) => return Err(GameError::Agent { player_idx, error }),
+    };
+
+    let seen_events = game_runtime.events().len();
+
+    game_runtime
+        .execute_action(action)
+        .map_err(|error| GameError::IllegalAction { player_idx, error })?;
+
+    for event in &game_runtime.events()[seen_events..] {
+        for agent in agents.iter_mut() {
+            agent.observe_event(event);
+        }
+    }
+
+    if let Some(winner) = game_runtime.winner() {
+        let view = OmniscientView::new(game_runtime);
+        for agent in agents.iter_mut() {
+            agent.observe_game_over(&view, winner);
+        }
+    }
+
+    Ok(Some(seen_events))
+}
+
+/// Runs the game to completion by asking each seat's agent for its next action, and returns the
+/// index of the winner.
+pub fn play_game(
+    game_runtime: &mut GameRuntime,
+    agents: &mut [Box<dyn Agent>],
+) -> Result<usize, GameError> {
+    loop {
+        if let Some(winner) = game_runtime.winner() {
+            return Ok(winner);
+        }
+
+        // Nothing else is going to make up the agent's mind while the game is played out here.
+        if play_next_action(game_runtime, agents)?.is_none() {
+            return Err(GameError::Agent {
+                player_idx: game_runtime.current_player_idx(),
+                error: AgentError::Waiting,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::ruleset::Ruleset;
+
+    /// Plays the last legal action, which is a card whenever there's one to play.
+    struct LastActionAgent;
+
+    impl Agent for LastActionAgent {
+        fn choose_action(
+            &mut self,
+            _view: &PlayerView,
+            legal_actions: &[Action],
+        ) -> Result<Action, AgentError> {
+            Ok(*legal_actions.last().unwrap())
+        }
+    }
+
+    struct IllegalAgent;
+
+    impl Agent for IllegalAgent {
+        fn choose_action(
+            &mut self,
+            _view: &PlayerView,
+            _legal_actions: &[Action],
+        ) -> Result<Action, AgentError> {
+            Ok(Action::CallBluff)
+        }
+    }
+
+    struct WaitingAgent;
+
+    impl Agent for WaitingAgent {
+        fn choose_action(
+            &mut self,
+            _view: &PlayerView,
+            _legal_actions: &[Action],
+        ) -> Result<Action, AgentError> {
+            Err(AgentError::Waiting)
+        }
+    }
+
+    #[test]
+    fn play_game_runs_until_someone_wins() {
+        let mut game_runtime = GameRuntime::new(3, Ruleset::default(), StdRng::seed_from_u64(7));
+        let mut agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(LastActionAgent),
+            Box::new(LastActionAgent),
+            Box::new(LastActionAgent),
+        ];
+
+        let winner = play_game(&mut game_runtime, &mut agents).unwrap();
+
+        assert_eq!(game_runtime.winner(), Some(winner));
+        assert!(game_runtime.hand(winner).is_empty());
+    }
+
+    #[test]
+    fn play_game_stops_on_illegal_action() {
+        let mut game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(7));
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(IllegalAgent), Box::new(IllegalAgent)];
+
+        assert!(matches!(
+            play_game(&mut game_runtime, &mut agents),
+            Err(GameError::IllegalAction {
+                player_idx: 0,
+                error: ActionError::IllegalAction(Action::CallBluff),
+            })
+        ));
+    }
+
+    #[test]
+    fn waiting_agents_leave_the_game_as_it_was() {
+        let mut game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(7));
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(WaitingAgent), Box::new(WaitingAgent)];
+
+        assert!(matches!(
+            play_next_action(&mut game_runtime, &mut agents),
+            Ok(None)
+        ));
+        assert_eq!(game_runtime.actions_executed(), 0);
+
+        // Nobody is going to answer for them when the game is played out in one go.
+        assert!(matches!(
+            play_game(&mut game_runtime, &mut agents),
+            Err(GameError::Agent {
+                player_idx: 0,
+                error: AgentError::Waiting,
+            })
+        ));
+    }
+}
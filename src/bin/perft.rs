@@ -0,0 +1,83 @@
+use std::fs;
+use std::process;
+use std::time::Instant;
+
+use uno::{Position, Ruleset};
+
+const USAGE: &str = "usage: perft [--position FILE | --players N] [--seed N] [--ruleset NAME] \
+                     [--depth N] [--divide]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    eprintln!("rulesets: {}", Ruleset::NAMES.join(", "));
+    process::exit(2);
+}
+
+/// Counts the lines of play from a position to each depth up to the given one.
+fn main() {
+    let mut position_path = None;
+    let mut num_players = 2;
+    let mut seed = 0;
+    let mut ruleset = Ruleset::default();
+    let mut depth = 4;
+    let mut divide = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--divide" {
+            divide = true;
+            continue;
+        }
+
+        let value = args.next().unwrap_or_else(|| exit_with_usage());
+        match (arg.as_str(), value.parse::<usize>().ok()) {
+            ("--position", _) => position_path = Some(value),
+            ("--players", Some(value)) if value >= 2 => num_players = value,
+            ("--seed", Some(value)) => seed = value as u64,
+            ("--depth", Some(value)) if value > 0 => depth = value,
+            ("--ruleset", _) => {
+                ruleset = Ruleset::by_name(&value).unwrap_or_else(|| exit_with_usage());
+            }
+            _ => exit_with_usage(),
+        }
+    }
+
+    if num_players > ruleset.max_players() {
+        exit_with_usage();
+    }
+
+    let position = match position_path {
+        Some(path) => {
+            let text = fs::read_to_string(&path).unwrap_or_else(|err| {
+                eprintln!("couldn't read {}: {}", path, err);
+                process::exit(1);
+            });
+            Position::parse(&text).unwrap_or_else(|err| {
+                eprintln!("{}: {}", path, err);
+                process::exit(1);
+            })
+        }
+        None => Position::dealt(num_players, ruleset, seed),
+    };
+
+    for depth in 1..=depth {
+        let started_at = Instant::now();
+        let nodes = position.perft(depth);
+        let elapsed = started_at.elapsed();
+
+        println!(
+            "depth {}: {} nodes in {:.3}s ({:.0} nodes/s)",
+            depth,
+            nodes,
+            elapsed.as_secs_f64(),
+            nodes as f64 / elapsed.as_secs_f64().max(1e-9)
+        );
+    }
+
+    if divide {
+        println!();
+        for (action, nodes) in position.divide(depth) {
+            println!("{}: {}", action, nodes);
+        }
+    }
+}
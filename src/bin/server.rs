@@ -0,0 +1,98 @@
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use uno::cli::{HubHandle, Server, ServerConfig, TimeoutAction, TurnTimer, WebServer, LOBBY_BOTS};
+
+const USAGE: &str =
+    "usage: server [--addr ADDR] [--web-addr ADDR] [--seed SEED] [--turn-seconds N] \
+                     [--on-timeout draw-and-pass|first-legal] [--bot-after N] \
+                     [--grace-seconds N] [--spectator-delay N] [--bot NAME]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+/// Hosts a lobby on the network until killed.
+fn main() {
+    let mut addr = "0.0.0.0:7878".to_owned();
+    let mut web_addr = None;
+    let mut config = ServerConfig {
+        seed: rand::random(),
+        ..ServerConfig::default()
+    };
+    let mut has_time_limit = false;
+    let mut turn_timer = TurnTimer {
+        turn_time: Duration::from_secs(30),
+        on_timeout: TimeoutAction::DrawAndPass,
+        replace_after: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| exit_with_usage());
+        match (arg.as_str(), value.parse::<u64>().ok()) {
+            ("--seed", Some(value)) => config.seed = value,
+            ("--turn-seconds", Some(value)) if value > 0 => {
+                turn_timer.turn_time = Duration::from_secs(value);
+                has_time_limit = true;
+            }
+            ("--bot-after", Some(value)) if value > 0 => {
+                turn_timer.replace_after = Some(value as usize)
+            }
+            ("--grace-seconds", Some(value)) => {
+                config.reconnect_grace = Duration::from_secs(value);
+            }
+            ("--spectator-delay", Some(value)) => {
+                config.spectator_delay = Duration::from_secs(value);
+            }
+            ("--addr", _) => addr = value,
+            ("--web-addr", _) => web_addr = Some(value),
+            ("--on-timeout", _) => {
+                turn_timer.on_timeout =
+                    TimeoutAction::by_name(&value).unwrap_or_else(|| exit_with_usage());
+            }
+            ("--bot", _) => {
+                config.replacement_bot = LOBBY_BOTS
+                    .into_iter()
+                    .find(|&name| name == value)
+                    .unwrap_or_else(|| exit_with_usage());
+            }
+            _ => exit_with_usage(),
+        }
+    }
+
+    // The other timer options only matter once there's a time limit.
+    config.turn_timer = has_time_limit.then_some(turn_timer);
+
+    let hub = HubHandle::spawn(config);
+
+    if let Some(web_addr) = web_addr {
+        let web_hub = hub.clone();
+        let result = WebServer::bind(&web_addr).and_then(|web_server| {
+            println!(
+                "serving the web client on http://{}",
+                web_server.local_addr()?
+            );
+            thread::spawn(move || web_server.run(web_hub));
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            eprintln!("server: {}", err);
+            process::exit(1);
+        }
+    }
+
+    let result = Server::bind(&addr).and_then(|server| {
+        println!("listening on {}", server.local_addr()?);
+        server.run(hub);
+        Ok(())
+    });
+
+    if let Err(err) = result {
+        eprintln!("server: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,56 @@
+use std::process;
+use std::thread;
+
+use uno::cli::{bot_by_name, simulate, SimulationConfig, BOT_NAMES};
+use uno::Ruleset;
+
+const USAGE: &str = "usage: simulate [--games N] [--seed START] [--bots NAME,NAME,...] \
+                     [--ruleset NAME] [--threads N]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    eprintln!("bots: {}", BOT_NAMES.join(", "));
+    eprintln!("rulesets: {}", Ruleset::NAMES.join(", "));
+    process::exit(2);
+}
+
+/// Plays a batch of bot-only games and prints how each seat did.
+fn main() {
+    let mut games = 1000u64;
+    let mut first_seed = 0u64;
+    let mut config = SimulationConfig {
+        bots: vec!["heuristic".to_owned(), "random".to_owned()],
+        ruleset: Ruleset::default(),
+        seeds: 0..0,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| exit_with_usage());
+        match (arg.as_str(), value.parse::<u64>().ok()) {
+            ("--games", Some(value)) => games = value,
+            ("--seed", Some(value)) => first_seed = value,
+            ("--threads", Some(value)) if value > 0 => config.threads = value as usize,
+            ("--bots", _) => config.bots = value.split(',').map(str::to_owned).collect(),
+            ("--ruleset", _) => {
+                config.ruleset = Ruleset::by_name(&value).unwrap_or_else(|| exit_with_usage());
+            }
+            _ => exit_with_usage(),
+        }
+    }
+
+    if config.bots.len() < 2 || config.bots.len() > config.ruleset.max_players() {
+        exit_with_usage();
+    }
+    for name in &config.bots {
+        if let Err(err) = bot_by_name(name, 0) {
+            eprintln!("simulate: {}", err);
+            process::exit(2);
+        }
+    }
+
+    config.seeds = first_seed..first_seed.saturating_add(games);
+
+    print!("{}", simulate(&config));
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+use uno::cli::{bot_by_name, run_tournament, Format, Ratings, TournamentConfig, BOT_NAMES};
+use uno::Ruleset;
+
+const USAGE: &str = "usage: tournament [--bots NAME,NAME,...] [--table-size N] \
+                     [--format round-robin|swiss [--rounds N]] [--ruleset NAME] [--seed START] \
+                     [--ratings FILE] [--threads N]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    eprintln!("bots: {}", BOT_NAMES.join(", "));
+    eprintln!("rulesets: {}", Ruleset::NAMES.join(", "));
+    process::exit(2);
+}
+
+/// Plays a tournament between bots and prints the updated ratings, which are kept in a file
+/// between runs.
+fn main() {
+    let mut config = TournamentConfig {
+        bots: vec![
+            "random".to_owned(),
+            "greedy".to_owned(),
+            "heuristic".to_owned(),
+        ],
+        table_size: 2,
+        format: Format::RoundRobin,
+        ruleset: Ruleset::default(),
+        first_seed: 0,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let mut rounds = None;
+    let mut swiss = false;
+    let mut ratings_path = PathBuf::from("ratings.txt");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| exit_with_usage());
+        match (arg.as_str(), value.parse::<u64>().ok()) {
+            ("--table-size", Some(value)) if value >= 2 => config.table_size = value as usize,
+            ("--rounds", Some(value)) => rounds = Some(value as usize),
+            ("--seed", Some(value)) => config.first_seed = value,
+            ("--threads", Some(value)) if value > 0 => config.threads = value as usize,
+            ("--bots", _) => config.bots = value.split(',').map(str::to_owned).collect(),
+            ("--format", _) if value == "round-robin" => swiss = false,
+            ("--format", _) if value == "swiss" => swiss = true,
+            ("--ratings", _) => ratings_path = PathBuf::from(value),
+            ("--ruleset", _) => {
+                config.ruleset = Ruleset::by_name(&value).unwrap_or_else(|| exit_with_usage());
+            }
+            _ => exit_with_usage(),
+        }
+    }
+
+    // A round robin plays every table once, so it has no rounds to count.
+    match (swiss, rounds) {
+        (true, rounds) => {
+            config.format = Format::Swiss {
+                rounds: rounds.unwrap_or(10),
+            }
+        }
+        (false, Some(_)) => exit_with_usage(),
+        (false, None) => {}
+    }
+
+    let has_duplicates = config
+        .bots
+        .iter()
+        .enumerate()
+        .any(|(idx, name)| config.bots[..idx].contains(name));
+
+    if config.bots.len() < config.table_size
+        || config.table_size > config.ruleset.max_players()
+        || has_duplicates
+    {
+        exit_with_usage();
+    }
+    for name in &config.bots {
+        if let Err(err) = bot_by_name(name, 0) {
+            eprintln!("tournament: {}", err);
+            process::exit(2);
+        }
+    }
+
+    let mut ratings = Ratings::load(&ratings_path).unwrap_or_else(|err| {
+        eprintln!("tournament: {}: {}", ratings_path.display(), err);
+        process::exit(1);
+    });
+
+    for (seed, table, err) in run_tournament(&config, &mut ratings) {
+        eprintln!("seed {} ({}): {}", seed, table.join(", "), err);
+    }
+
+    if let Err(err) = ratings.save(&ratings_path) {
+        eprintln!("tournament: {}: {}", ratings_path.display(), err);
+        process::exit(1);
+    }
+
+    println!("{:<12}{:>8}{:>8}", "bot", "rating", "games");
+    for (name, rating) in ratings.standings() {
+        println!("{:<12}{:>8.1}{:>8}", name, rating.rating, rating.games);
+    }
+}
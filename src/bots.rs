@@ -0,0 +1,479 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::agent::{Agent, AgentError};
+use crate::engine::ExternalAgent;
+use crate::game_executor::Event;
+use crate::game_state::{Action, Card, CardColor, CardKind, Direction, WildCardKind};
+use crate::mcts::{self, MctsBot};
+use crate::tracker::CardTracker;
+use crate::view::PlayerView;
+
+pub const BOT_NAMES: [&str; 4] = ["random", "greedy", "heuristic", "mcts"];
+
+/// Bot names starting with this run an external engine, e.g. `engine:./my-engine --fast`. Quote
+/// paths or arguments with spaces in them, as in `engine:'./my engine' --fast`.
+pub(crate) const ENGINE_PREFIX: &str = "engine:";
+
+/// Wild cards are worth 50 points at the end of a round, numeric cards their face value.
+const WILD_CARD_SCORE: u32 = 50;
+
+/// The heuristic bot calls a bluff once it's more likely than not that the player who played the
+/// draw four was holding a card of the previous color.
+const CALL_BLUFF_THRESHOLD: f64 = 0.5;
+
+/// One of `BOT_NAMES`, or `engine:<command>` for an external engine, which gets started right
+/// away. The error says why the bot couldn't be had.
+pub fn bot_by_name(name: &str, seed: u64) -> Result<Box<dyn Agent>, String> {
+    let rng = StdRng::seed_from_u64(seed);
+
+    if let Some(command_line) = name.strip_prefix(ENGINE_PREFIX) {
+        return match ExternalAgent::spawn_command_line(command_line) {
+            Ok(agent) => Ok(Box::new(agent)),
+            Err(err) => Err(format!("{}: {}", command_line, err)),
+        };
+    }
+
+    match name {
+        "random" => Ok(Box::new(RandomBot { rng })),
+        "greedy" => Ok(Box::new(GreedyBot { rng })),
+        "heuristic" => Ok(Box::new(HeuristicBot {
+            rng,
+            active_color: None,
+            color_before_draw_four: None,
+            tracker: CardTracker::new(),
+        })),
+        "mcts" => Ok(Box::new(MctsBot::new(seed, mcts::DEFAULT_BUDGET))),
+        _ => Err(format!("unknown bot {}", name)),
+    }
+}
+
+pub(crate) fn card_score(card: Card) -> u32 {
+    match card {
+        Card::Normal(normal_card) => match normal_card.kind {
+            CardKind::Numeric(numeric) => numeric.value() as u32,
+        },
+        Card::Special(_) => WILD_CARD_SCORE,
+    }
+}
+
+/// The color the hand holds the most cards of, ties broken by the order of `CardColor::ALL`.
+pub(crate) fn most_held_color(hand: &[Card]) -> CardColor {
+    let mut best_color = CardColor::ALL[0];
+    let mut best_count = 0;
+
+    for color in CardColor::ALL {
+        let count = hand
+            .iter()
+            .filter(|card| card.get_color() == Some(color))
+            .count();
+
+        if count > best_count {
+            best_color = color;
+            best_count = count;
+        }
+    }
+
+    best_color
+}
+
+/// Picks the action with the highest score, breaking ties at random.
+fn best_action_by<F>(rng: &mut StdRng, legal_actions: &[Action], mut score: F) -> Action
+where
+    F: FnMut(Action) -> i64,
+{
+    let best_score = legal_actions.iter().map(|&a| score(a)).max().unwrap();
+    let best_actions: Vec<Action> = legal_actions
+        .iter()
+        .copied()
+        .filter(|&a| score(a) == best_score)
+        .collect();
+
+    *best_actions.choose(rng).unwrap()
+}
+
+/// Plays uniformly at random among the legal actions.
+pub(crate) struct RandomBot {
+    rng: StdRng,
+}
+
+impl Agent for RandomBot {
+    fn choose_action(
+        &mut self,
+        _view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        Ok(*legal_actions.choose(&mut self.rng).unwrap())
+    }
+}
+
+/// Gets rid of its most valuable cards first, so it loses as few points as possible if someone
+/// else goes out. Never calls a bluff.
+pub(crate) struct GreedyBot {
+    rng: StdRng,
+}
+
+impl Agent for GreedyBot {
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        let color = most_held_color(&view.hand);
+
+        Ok(best_action_by(
+            &mut self.rng,
+            legal_actions,
+            |action| match action {
+                Action::Play { card } => 1 + card_score(card) as i64,
+                Action::ChooseColor { color: chosen } if chosen == color => 1,
+                _ => 0,
+            },
+        ))
+    }
+}
+
+/// Plays numeric cards of the color it holds the most of, and saves wild cards for when it's
+/// stuck or the game is about to end.
+pub(crate) struct HeuristicBot {
+    rng: StdRng,
+    active_color: Option<CardColor>,
+    color_before_draw_four: Option<CardColor>,
+    tracker: CardTracker,
+}
+
+impl HeuristicBot {
+    fn should_call_bluff(&self, view: &PlayerView) -> bool {
+        let color = match self.color_before_draw_four {
+            Some(color) => color,
+            None => return false,
+        };
+
+        let num_players = view.table.hand_sizes.len();
+        let bluffer_idx = match view.table.direction() {
+            Direction::Forward => (view.player_idx + num_players - 1) % num_players,
+            Direction::Backward => (view.player_idx + 1) % num_players,
+        };
+
+        self.tracker.probability_holds_color(bluffer_idx, color) > CALL_BLUFF_THRESHOLD
+    }
+}
+
+impl Agent for HeuristicBot {
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        self.tracker.update(view);
+
+        if legal_actions.contains(&Action::CallBluff) {
+            return Ok(if self.should_call_bluff(view) {
+                Action::CallBluff
+            } else {
+                legal_actions[0]
+            });
+        }
+
+        // The card that starts the game is never observed as an action, so the active color is
+        // also picked up from the view.
+        self.active_color = view.table.active_color;
+
+        let color = most_held_color(&view.hand);
+        let endgame = view.hand.len() <= 2
+            || view
+                .table
+                .hand_sizes
+                .iter()
+                .enumerate()
+                .any(|(idx, &size)| idx != view.player_idx && size <= 2);
+
+        Ok(best_action_by(
+            &mut self.rng,
+            legal_actions,
+            |action| match action {
+                Action::Play {
+                    card: Card::Normal(normal_card),
+                } => {
+                    let same_color = (normal_card.color == color) as i64;
+                    100 + 20 * same_color + card_score(Card::Normal(normal_card)) as i64
+                }
+                Action::Play {
+                    card: Card::Special(wild_card),
+                } => {
+                    let draw_four = (wild_card.kind == WildCardKind::DrawFour) as i64;
+                    if endgame {
+                        200 + draw_four
+                    } else {
+                        50 + draw_four
+                    }
+                }
+                Action::ChooseColor { color: chosen } if chosen == color => 1,
+                _ => 0,
+            },
+        ))
+    }
+
+    fn observe_event(&mut self, event: &Event) {
+        self.tracker.observe_event(event);
+
+        match *event {
+            Event::Played {
+                card: Card::Normal(normal_card),
+                ..
+            } => self.active_color = Some(normal_card.color),
+            Event::Played {
+                card: Card::Special(wild_card),
+                ..
+            } if wild_card.kind == WildCardKind::DrawFour => {
+                self.color_before_draw_four = self.active_color;
+            }
+            Event::ChoseColor { color, .. } => self.active_color = Some(color),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::agent::play_game;
+    use crate::game_executor::GameRuntime;
+    use crate::game_state::*;
+    use crate::ruleset::Ruleset;
+    use crate::view::SpectatorView;
+
+    fn view_with_hand<'a>(hand: &'a [Card], game_state: &'a GameState) -> PlayerView<'a> {
+        PlayerView {
+            player_idx: 0,
+            hand: hand.to_vec(),
+            game_state: *game_state,
+            table: SpectatorView {
+                current_player_idx: 0,
+                hand_sizes: vec![hand.len(), 7],
+                game_state: *game_state,
+                discard_pile: std::slice::from_ref(&game_state.last_played_card),
+                active_color: game_state
+                    .last_played_card
+                    .get_color()
+                    .or(game_state.chosen_color),
+                drawing_deck_len: 50,
+                events: &[],
+                winner: None,
+                ruleset: Ruleset::default(),
+            },
+        }
+    }
+
+    fn start_of_game(last_played_card: Card) -> GameState {
+        GameState {
+            last_action: Action::Play {
+                card: last_played_card,
+            },
+            last_played_card,
+            last_drew_card: None,
+            chosen_color: None,
+            direction: Direction::Forward,
+        }
+    }
+
+    #[test]
+    fn bots_are_selectable_by_name() {
+        for name in BOT_NAMES {
+            assert!(bot_by_name(name, 0).is_ok());
+        }
+
+        assert_eq!(
+            bot_by_name("nobody", 0).err(),
+            Some("unknown bot nobody".to_owned())
+        );
+    }
+
+    #[test]
+    fn bots_play_complete_games() {
+        // The search bot is too slow for a full game at its default budget in a debug build.
+        for name in BOT_NAMES.into_iter().filter(|&name| name != "mcts") {
+            let mut game_runtime =
+                GameRuntime::new(3, Ruleset::default(), StdRng::seed_from_u64(3));
+            let mut agents: Vec<Box<dyn Agent>> = (0..3)
+                .map(|seed| bot_by_name(name, seed).unwrap())
+                .collect();
+
+            assert!(play_game(&mut game_runtime, &mut agents).is_ok());
+        }
+    }
+
+    #[test]
+    fn greedy_bot_plays_most_valuable_card() {
+        let blue_zero_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Zero),
+            color: CardColor::Blue,
+        });
+
+        let blue_two_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Two),
+            color: CardColor::Blue,
+        });
+
+        let colorchooser_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+
+        let hand = [blue_two_card, colorchooser_card];
+        let game_state = start_of_game(blue_zero_card);
+        let legal_actions = game_state.get_actions_for_deck(&Deck {
+            cards: hand.to_vec(),
+        });
+
+        let mut bot = bot_by_name("greedy", 0).unwrap();
+
+        assert_eq!(
+            bot.choose_action(&view_with_hand(&hand, &game_state), &legal_actions)
+                .unwrap(),
+            Action::Play {
+                card: colorchooser_card
+            }
+        );
+    }
+
+    #[test]
+    fn heuristic_bot_keeps_wild_cards_for_the_endgame() {
+        let blue_zero_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Zero),
+            color: CardColor::Blue,
+        });
+
+        let blue_two_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Two),
+            color: CardColor::Blue,
+        });
+
+        let red_three_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Three),
+            color: CardColor::Red,
+        });
+
+        let colorchooser_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+
+        let hand = [blue_two_card, colorchooser_card, red_three_card];
+        let game_state = start_of_game(blue_zero_card);
+        let legal_actions = game_state.get_actions_for_deck(&Deck {
+            cards: hand.to_vec(),
+        });
+
+        let mut bot = bot_by_name("heuristic", 0).unwrap();
+
+        assert_eq!(
+            bot.choose_action(&view_with_hand(&hand, &game_state), &legal_actions)
+                .unwrap(),
+            Action::Play {
+                card: blue_two_card
+            }
+        );
+
+        let hand = [blue_two_card, colorchooser_card];
+        let legal_actions = game_state.get_actions_for_deck(&Deck {
+            cards: hand.to_vec(),
+        });
+
+        assert_eq!(
+            bot.choose_action(&view_with_hand(&hand, &game_state), &legal_actions)
+                .unwrap(),
+            Action::Play {
+                card: colorchooser_card
+            }
+        );
+    }
+
+    #[test]
+    fn heuristic_bot_chooses_color_it_holds_most_of() {
+        let colorchooser_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+
+        let hand = [
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Two),
+                color: CardColor::Yellow,
+            }),
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Five),
+                color: CardColor::Yellow,
+            }),
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Five),
+                color: CardColor::Green,
+            }),
+        ];
+        let game_state = start_of_game(colorchooser_card);
+        let legal_actions = game_state.get_actions_for_deck(&Deck {
+            cards: hand.to_vec(),
+        });
+
+        let mut bot = bot_by_name("heuristic", 0).unwrap();
+
+        assert_eq!(
+            bot.choose_action(&view_with_hand(&hand, &game_state), &legal_actions)
+                .unwrap(),
+            Action::ChooseColor {
+                color: CardColor::Yellow
+            }
+        );
+    }
+
+    #[test]
+    fn heuristic_bot_calls_bluff_on_large_hands_only() {
+        let draw_four_card = Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        });
+
+        let blue_zero_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Zero),
+            color: CardColor::Blue,
+        });
+
+        let game_state = GameState {
+            last_action: Action::ChooseColor {
+                color: CardColor::Red,
+            },
+            last_played_card: draw_four_card,
+            last_drew_card: None,
+            chosen_color: Some(CardColor::Red),
+            direction: Direction::Forward,
+        };
+        let hand = [blue_zero_card];
+        let legal_actions = vec![
+            Action::DrawCard {
+                amount: DrawAmount::Four,
+            },
+            Action::CallBluff,
+        ];
+
+        for (bluffer_hand_size, expected_action) in [(1, legal_actions[0]), (10, Action::CallBluff)]
+        {
+            let mut bot = bot_by_name("heuristic", 0).unwrap();
+            bot.observe_event(&Event::Played {
+                player_idx: 1,
+                card: blue_zero_card,
+            });
+            bot.observe_event(&Event::Played {
+                player_idx: 1,
+                card: draw_four_card,
+            });
+
+            let mut view = view_with_hand(&hand, &game_state);
+            view.table.hand_sizes = vec![1, bluffer_hand_size];
+
+            assert_eq!(
+                bot.choose_action(&view, &legal_actions).unwrap(),
+                expected_action
+            );
+        }
+    }
+}
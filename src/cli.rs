@@ -0,0 +1,15 @@
+pub use crate::agent::{play_game, Agent, AgentError, GameError};
+pub use crate::bots::{bot_by_name, BOT_NAMES};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::lobby::LOBBY_BOTS;
+pub use crate::mcts::{MctsBot, SearchBudget};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::server::{HubHandle, Server, ServerConfig};
+pub use crate::simulator::{simulate, SimulationConfig, SimulationReport};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::timer::{TimeoutAction, TurnTimer};
+pub use crate::tournament::{run_tournament, Format, Rating, Ratings, Table, TournamentConfig};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::tui::{human_agents, TuiAgent};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::web::WebServer;
@@ -0,0 +1,410 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::agent::{Agent, AgentError};
+use crate::game_executor::Event;
+use crate::game_state::Action;
+use crate::notation;
+use crate::view::{OmniscientView, PlayerView};
+
+/// How long an engine gets to start up and to answer each move.
+pub(crate) const DEFAULT_ENGINE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long an engine gets to exit on its own after being told to quit.
+const QUIT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Plays through an external program, talking to it over its stdin and stdout one line at a time,
+/// with cards, actions and events written in `notation`:
+///
+/// - `uno` is sent once on startup. The engine answers `uno ok`.
+/// - `newgame <number of players> <seat>` and `ruleset <initial hand size> <bluff challenges>
+///   <forced play after draw>` start each game.
+/// - `event <event>` tells what happened at the table since the engine's last move.
+/// - `hand <cards>` and `top <card> <active color or none>` come before every move.
+/// - `go <legal actions>` asks for a move. The engine answers `move <action>`.
+/// - `gameover <winner>` ends each game, and `quit` the session.
+///
+/// Anything else the engine prints, such as `info` lines, is ignored.
+pub(crate) struct ExternalAgent {
+    child: Child,
+    /// Only taken when shutting the engine down.
+    stdin: Option<ChildStdin>,
+    lines: Receiver<String>,
+    timeout: Duration,
+    /// Events since the engine last moved, sent along when it's next asked to.
+    pending_events: Vec<Event>,
+    in_game: bool,
+    /// Set once the engine crashed or stopped answering, after which it isn't asked anything.
+    dead: bool,
+}
+
+impl ExternalAgent {
+    /// Starts the engine and waits for it to say it speaks the protocol.
+    pub(crate) fn spawn(mut command: Command, timeout: Duration) -> io::Result<ExternalAgent> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut agent = ExternalAgent {
+            child,
+            stdin: Some(stdin),
+            lines,
+            timeout,
+            pending_events: vec![],
+            in_game: false,
+            dead: false,
+        };
+
+        agent
+            .send("uno")
+            .and_then(|()| agent.handshake())
+            .map_err(|err| io::Error::other(format!("engine didn't start: {}", err)))?;
+
+        Ok(agent)
+    }
+
+    /// Runs `command_line`, split into the program and its arguments by `split_command_line`.
+    pub(crate) fn spawn_command_line(command_line: &str) -> io::Result<ExternalAgent> {
+        let words = split_command_line(command_line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unclosed quote in engine command",
+            )
+        })?;
+        let (program, args) = words
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty engine command"))?;
+
+        let mut command = Command::new(program);
+        command.args(args);
+        ExternalAgent::spawn(command, DEFAULT_ENGINE_TIMEOUT)
+    }
+
+    fn handshake(&mut self) -> Result<(), AgentError> {
+        let deadline = Instant::now() + self.timeout;
+        while self.receive(deadline)?.trim() != "uno ok" {}
+
+        Ok(())
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), AgentError> {
+        let Some(stdin) = &mut self.stdin else {
+            return Err(AgentError::Left);
+        };
+
+        if writeln!(stdin, "{}", line)
+            .and_then(|()| stdin.flush())
+            .is_err()
+        {
+            self.dead = true;
+            return Err(AgentError::Crashed);
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the engine's next line, giving up on it at `deadline`.
+    fn receive(&mut self, deadline: Instant) -> Result<String, AgentError> {
+        match self
+            .lines
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => {
+                // A late answer would be taken for the answer to the next question.
+                self.dead = true;
+                let _ = self.child.kill();
+                Err(AgentError::TimedOut)
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                self.dead = true;
+                Err(AgentError::Crashed)
+            }
+        }
+    }
+
+    fn send_pending_events(&mut self) -> Result<(), AgentError> {
+        for event in std::mem::take(&mut self.pending_events) {
+            self.send(&format!("event {}", notation::format_event(event)))?;
+        }
+
+        Ok(())
+    }
+
+    fn request_move(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        if !self.in_game {
+            let ruleset = view.table.ruleset;
+            self.send(&format!(
+                "newgame {} {}",
+                view.table.hand_sizes.len(),
+                view.player_idx
+            ))?;
+            self.send(&format!(
+                "ruleset {} {} {}",
+                ruleset.initial_hand_size, ruleset.bluff_challenges, ruleset.forced_play_after_draw
+            ))?;
+            self.in_game = true;
+        }
+
+        self.send_pending_events()?;
+
+        let hand: Vec<String> = view
+            .hand
+            .iter()
+            .map(|&card| notation::format_card(card))
+            .collect();
+        self.send(&format!("hand {}", hand.join(" ")))?;
+        self.send(&format!(
+            "top {} {}",
+            notation::format_card(view.table.top_card()),
+            view.table
+                .active_color
+                .map_or("none", notation::format_color)
+        ))?;
+
+        let actions: Vec<String> = legal_actions
+            .iter()
+            .map(|&action| notation::format_action(action))
+            .collect();
+        self.send(&format!("go {}", actions.join(" ")))?;
+
+        let deadline = Instant::now() + self.timeout;
+        let reply = loop {
+            if let Some(reply) = self.receive(deadline)?.strip_prefix("move ") {
+                break reply.trim().to_owned();
+            }
+        };
+
+        notation::parse_action(&reply)
+            .filter(|action| legal_actions.contains(action))
+            .ok_or(AgentError::InvalidReply(reply))
+    }
+}
+
+impl Agent for ExternalAgent {
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        if self.dead {
+            return Err(AgentError::Left);
+        }
+
+        self.request_move(view, legal_actions)
+    }
+
+    fn observe_event(&mut self, event: &Event) {
+        self.pending_events.push(*event);
+    }
+
+    fn observe_game_over(&mut self, _view: &OmniscientView, winner: usize) {
+        if self.dead || !self.in_game {
+            self.pending_events.clear();
+            return;
+        }
+
+        self.in_game = false;
+        let _ = self
+            .send_pending_events()
+            .and_then(|()| self.send(&format!("gameover {}", winner)));
+    }
+}
+
+impl Drop for ExternalAgent {
+    fn drop(&mut self) {
+        if !self.dead {
+            let _ = self.send("quit");
+        }
+        self.stdin = None;
+
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while let Ok(None) = self.child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = self.child.kill();
+                let _ = self.child.wait();
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Splits `command_line` at whitespace, except inside single or double quotes, which let paths
+/// and arguments have spaces in them. There are no escapes. `None` if a quote is left open.
+fn split_command_line(command_line: &str) -> Option<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+
+    for c in command_line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return None;
+    }
+    words.extend(word);
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::agent::play_game;
+    use crate::bots;
+    use crate::game_executor::GameRuntime;
+    use crate::ruleset::Ruleset;
+
+    /// Answers the handshake, then runs `on_go` whenever asked to move, with the legal actions
+    /// as its arguments.
+    fn shell_engine(on_go: &str, timeout: Duration) -> io::Result<ExternalAgent> {
+        let script = format!(
+            "while read command args; do
+                case $command in
+                    uno) echo 'info starting'; echo 'uno ok' ;;
+                    go) set -- $args; {} ;;
+                    quit) exit ;;
+                esac
+            done",
+            on_go
+        );
+
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        ExternalAgent::spawn(command, timeout)
+    }
+
+    fn new_game() -> GameRuntime {
+        GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(9))
+    }
+
+    #[test]
+    fn external_engines_play_whole_games() {
+        let transcript_path =
+            std::env::temp_dir().join(format!("uno-engine-{}.txt", process::id()));
+
+        // Plays the last legal action, which is a card whenever there's one to play, and keeps a
+        // transcript of everything it was sent.
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!(
+            "tee {} | while read command args; do
+                case $command in
+                    uno) echo 'uno ok' ;;
+                    go) for action in $args; do last=$action; done; echo \"move $last\" ;;
+                esac
+            done",
+            transcript_path.display()
+        ));
+        let engine = ExternalAgent::spawn(command, DEFAULT_ENGINE_TIMEOUT).unwrap();
+
+        let mut game_runtime = new_game();
+        let mut agents: Vec<Box<dyn Agent>> =
+            vec![Box::new(engine), bots::bot_by_name("random", 1).unwrap()];
+        let winner = play_game(&mut game_runtime, &mut agents).unwrap();
+        drop(agents);
+
+        let transcript = fs::read_to_string(&transcript_path).unwrap();
+        fs::remove_file(transcript_path).unwrap();
+        let lines: Vec<&str> = transcript.lines().collect();
+        assert_eq!(lines[..3], ["uno", "newgame 2 0", "ruleset 7 true false"]);
+        assert!(lines[3].starts_with("hand "));
+        assert!(lines[4].starts_with("top "));
+        assert!(lines[5].starts_with("go "));
+        assert!(lines.iter().any(|line| line.starts_with("event played 1 ")));
+        assert!(lines.contains(&format!("gameover {}", winner).as_str()));
+        assert_eq!(lines.last(), Some(&"quit"));
+    }
+
+    #[test]
+    fn engines_that_crash_stall_or_reply_nonsense_are_caught() {
+        let game_runtime = new_game();
+        let view = PlayerView::new(&game_runtime, 0);
+        let legal_actions = game_runtime.legal_actions();
+        let timeout = Duration::from_millis(200);
+
+        let mut engine = shell_engine("echo 'move r10'", timeout).unwrap();
+        assert!(matches!(
+            engine.choose_action(&view, &legal_actions),
+            Err(AgentError::InvalidReply(reply)) if reply == "r10"
+        ));
+        // A well-formed action still has to be one of the legal ones.
+        let mut engine = shell_engine("echo 'move bluff'", timeout).unwrap();
+        assert!(matches!(
+            engine.choose_action(&view, &legal_actions),
+            Err(AgentError::InvalidReply(_))
+        ));
+
+        let mut engine = shell_engine("exit 1", timeout).unwrap();
+        assert!(matches!(
+            engine.choose_action(&view, &legal_actions),
+            Err(AgentError::Crashed)
+        ));
+        assert!(matches!(
+            engine.choose_action(&view, &legal_actions),
+            Err(AgentError::Left)
+        ));
+
+        let mut engine = shell_engine("sleep 5", timeout).unwrap();
+        assert!(matches!(
+            engine.choose_action(&view, &legal_actions),
+            Err(AgentError::TimedOut)
+        ));
+
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 5");
+        assert!(ExternalAgent::spawn(command, timeout).is_err());
+        assert!(ExternalAgent::spawn_command_line("").is_err());
+    }
+
+    #[test]
+    fn command_lines_keep_quoted_spaces() {
+        assert_eq!(
+            split_command_line(r#"  "./my engine" --name 'Big Bot' --empty "" --fast"#),
+            Some(
+                ["./my engine", "--name", "Big Bot", "--empty", "", "--fast"]
+                    .map(String::from)
+                    .to_vec()
+            )
+        );
+        assert_eq!(split_command_line("   "), Some(vec![]));
+        assert_eq!(split_command_line("./engine 'oops"), None);
+    }
+}
@@ -0,0 +1,393 @@
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::game_executor::{ActionError, GameRuntime};
+use crate::game_state::{Action, Card, CardColor, DrawAmount, WildCard, WildCardKind};
+use crate::packed::{self, PackedCard, PackedHand};
+use crate::ruleset::Ruleset;
+use crate::view::PlayerView;
+
+/// Ten numerics in each of the four colors, then the two wild cards.
+pub const NUM_CARD_TYPES: usize = packed::NUM_CARD_TYPES;
+
+/// The largest table an observation has room for.
+pub const MAX_PLAYERS: usize = 10;
+
+/// Playing each card type, choosing each color, drawing one or four, calling a bluff and passing.
+pub const NUM_ACTIONS: usize = NUM_CARD_TYPES + 4 + 2 + 2;
+
+const CHOOSE_COLOR_OFFSET: usize = NUM_CARD_TYPES;
+const DRAW_ONE_INDEX: usize = CHOOSE_COLOR_OFFSET + 4;
+const DRAW_FOUR_INDEX: usize = DRAW_ONE_INDEX + 1;
+const CALL_BLUFF_INDEX: usize = DRAW_FOUR_INDEX + 1;
+const PASS_INDEX: usize = CALL_BLUFF_INDEX + 1;
+
+/// Hand counts per card type, the top card, the active color, the opponents' hand sizes starting
+/// from the next seat, and four flags: choosing a color, holding a freshly drawn card, facing a
+/// draw four, and how full the drawing deck is.
+pub const OBSERVATION_LEN: usize = NUM_CARD_TYPES + NUM_CARD_TYPES + 4 + (MAX_PLAYERS - 1) + 4;
+
+pub(crate) fn card_index(card: Card) -> usize {
+    PackedCard::new(card).index()
+}
+
+pub(crate) fn card_from_index(idx: usize) -> Option<Card> {
+    PackedCard::from_index(idx).map(PackedCard::unpack)
+}
+
+fn color_index(color: CardColor) -> usize {
+    color as usize
+}
+
+/// The index of `action` in the action space, or `None` for draws no player can choose.
+pub(crate) fn action_index(action: Action) -> Option<usize> {
+    match action {
+        Action::Play { card } => Some(card_index(card)),
+        Action::ChooseColor { color } => Some(CHOOSE_COLOR_OFFSET + color_index(color)),
+        Action::DrawCard {
+            amount: DrawAmount::One,
+        } => Some(DRAW_ONE_INDEX),
+        Action::DrawCard {
+            amount: DrawAmount::Four,
+        } => Some(DRAW_FOUR_INDEX),
+        Action::DrawCard { .. } => None,
+        Action::CallBluff => Some(CALL_BLUFF_INDEX),
+        Action::Pass => Some(PASS_INDEX),
+    }
+}
+
+pub(crate) fn action_from_index(idx: usize) -> Option<Action> {
+    match idx {
+        _ if idx < NUM_CARD_TYPES => card_from_index(idx).map(|card| Action::Play { card }),
+        _ if idx < DRAW_ONE_INDEX => Some(Action::ChooseColor {
+            color: CardColor::ALL[idx - CHOOSE_COLOR_OFFSET],
+        }),
+        DRAW_ONE_INDEX => Some(Action::DrawCard {
+            amount: DrawAmount::One,
+        }),
+        DRAW_FOUR_INDEX => Some(Action::DrawCard {
+            amount: DrawAmount::Four,
+        }),
+        CALL_BLUFF_INDEX => Some(Action::CallBluff),
+        PASS_INDEX => Some(Action::Pass),
+        _ => None,
+    }
+}
+
+/// Encodes what `view`'s player can see as a fixed-length feature vector.
+pub(crate) fn encode_view(view: &PlayerView) -> Vec<f32> {
+    let mut features = vec![0.0; OBSERVATION_LEN];
+
+    for (card, count) in PackedHand::from_cards(&view.hand).iter() {
+        features[card.index()] = count as f32;
+    }
+
+    let mut offset = NUM_CARD_TYPES;
+    features[offset + card_index(view.table.top_card())] = 1.0;
+
+    offset += NUM_CARD_TYPES;
+    if let Some(color) = view.table.active_color {
+        features[offset + color_index(color)] = 1.0;
+    }
+
+    offset += 4;
+    let num_players = view.table.hand_sizes.len();
+    for seat in 1..num_players.min(MAX_PLAYERS) {
+        let opponent_idx = (view.player_idx + seat) % num_players;
+        features[offset + seat - 1] = view.table.hand_sizes[opponent_idx] as f32;
+    }
+
+    offset += MAX_PLAYERS - 1;
+    let game_state = view.game_state;
+    let is_current_player = view.player_idx == view.table.current_player_idx;
+    let facing_draw_four = matches!(
+        (game_state.last_action, game_state.last_played_card),
+        (
+            Action::ChooseColor { .. },
+            Card::Special(WildCard {
+                kind: WildCardKind::DrawFour
+            })
+        )
+    );
+
+    features[offset] = (is_current_player
+        && matches!(
+            game_state.last_action,
+            Action::Play {
+                card: Card::Special(_)
+            }
+        )) as u8 as f32;
+    features[offset + 1] = game_state.last_drew_card.is_some() as u8 as f32;
+    features[offset + 2] = (is_current_player && facing_draw_four) as u8 as f32;
+    features[offset + 3] = view.table.drawing_deck_len as f32 / 84.0;
+
+    features
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Observation {
+    /// The player who acts next, and whose point of view `features` encodes.
+    pub player_idx: usize,
+    pub features: Vec<f32>,
+    /// Which action indices `step` accepts right now.
+    pub legal_mask: Vec<bool>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Step {
+    pub observation: Observation,
+    /// The reward of the player who took the action.
+    pub reward: f32,
+    /// Every seat's reward, for training all sides of a self-play game at once.
+    pub rewards: Vec<f32>,
+    pub done: bool,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum EnvError {
+    /// Fewer than two players, or more than observations or the ruleset have room for.
+    InvalidPlayerCount(usize),
+    InvalidActionIndex(usize),
+    IllegalAction(usize),
+    GameIsOver,
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvError::InvalidPlayerCount(num_players) => {
+                write!(f, "a game can't be played by {} players", num_players)
+            }
+            EnvError::InvalidActionIndex(idx) => write!(f, "no action has index {}", idx),
+            EnvError::IllegalAction(idx) => write!(f, "action {} is not legal right now", idx),
+            EnvError::GameIsOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+/// A gym-style environment where every seat is driven through the same `step` calls, so a single
+/// policy can play against itself. The winner gets a reward of 1 and the other players share a
+/// reward of -1 between them.
+pub struct Environment {
+    num_players: usize,
+    ruleset: Ruleset,
+    game_runtime: GameRuntime,
+}
+
+impl Environment {
+    /// Observations have room for at most `MAX_PLAYERS` players, which some rulesets could
+    /// otherwise deal in.
+    pub fn new(num_players: usize, ruleset: Ruleset, seed: u64) -> Result<Environment, EnvError> {
+        if num_players < 2 || num_players > MAX_PLAYERS.min(ruleset.max_players()) {
+            return Err(EnvError::InvalidPlayerCount(num_players));
+        }
+
+        Ok(Environment {
+            num_players,
+            ruleset,
+            game_runtime: GameRuntime::new(num_players, ruleset, StdRng::seed_from_u64(seed)),
+        })
+    }
+
+    /// Deals a new game and returns the first player's observation.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game_runtime =
+            GameRuntime::new(self.num_players, self.ruleset, StdRng::seed_from_u64(seed));
+        self.observation()
+    }
+
+    /// Takes the action with index `action_idx` for the current player.
+    pub fn step(&mut self, action_idx: usize) -> Result<Step, EnvError> {
+        let action =
+            action_from_index(action_idx).ok_or(EnvError::InvalidActionIndex(action_idx))?;
+        let player_idx = self.game_runtime.current_player_idx();
+
+        self.game_runtime
+            .execute_action(action)
+            .map_err(|err| match err {
+                ActionError::GameIsOver => EnvError::GameIsOver,
+                ActionError::IllegalAction(_) => EnvError::IllegalAction(action_idx),
+            })?;
+
+        let rewards = self.rewards();
+
+        Ok(Step {
+            observation: self.observation(),
+            reward: rewards[player_idx],
+            rewards,
+            done: self.game_runtime.winner().is_some(),
+        })
+    }
+
+    /// The current player's observation.
+    pub fn observation(&self) -> Observation {
+        self.observe(self.game_runtime.current_player_idx())
+    }
+
+    /// Any seat's observation, such as every loser's final one once the game is over.
+    pub fn observe(&self, player_idx: usize) -> Observation {
+        let view = PlayerView::new(&self.game_runtime, player_idx);
+        let mut legal_mask = vec![false; NUM_ACTIONS];
+
+        if player_idx == self.game_runtime.current_player_idx() {
+            for action in self.game_runtime.legal_actions() {
+                legal_mask[action_index(action).unwrap()] = true;
+            }
+        }
+
+        Observation {
+            player_idx,
+            features: encode_view(&view),
+            legal_mask,
+        }
+    }
+
+    fn rewards(&self) -> Vec<f32> {
+        let mut rewards = vec![0.0; self.num_players];
+
+        if let Some(winner) = self.game_runtime.winner() {
+            for (player_idx, reward) in rewards.iter_mut().enumerate() {
+                *reward = if player_idx == winner {
+                    1.0
+                } else {
+                    -1.0 / (self.num_players - 1) as f32
+                };
+            }
+        }
+
+        rewards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::seq::SliceRandom;
+
+    use super::*;
+    use crate::game_state::Deck;
+
+    #[test]
+    fn every_action_index_round_trips() {
+        for idx in 0..NUM_ACTIONS {
+            let action = action_from_index(idx).unwrap();
+            assert_eq!(action_index(action), Some(idx));
+        }
+
+        assert_eq!(action_from_index(NUM_ACTIONS), None);
+
+        for card in Deck::standard().cards {
+            assert_eq!(card_from_index(card_index(card)), Some(card));
+        }
+    }
+
+    #[test]
+    fn legal_mask_matches_legal_actions() {
+        let environment = Environment::new(3, Ruleset::default(), 4).unwrap();
+        let observation = environment.observation();
+
+        let legal_actions: Vec<Action> = (0..NUM_ACTIONS)
+            .filter(|&idx| observation.legal_mask[idx])
+            .map(|idx| action_from_index(idx).unwrap())
+            .collect();
+
+        let expected = environment.game_runtime.legal_actions();
+
+        assert!(expected.iter().all(|action| legal_actions.contains(action)));
+        assert!(legal_actions.iter().all(|action| expected.contains(action)));
+        assert!(environment
+            .observe(1)
+            .legal_mask
+            .iter()
+            .all(|&legal| !legal));
+    }
+
+    #[test]
+    fn observation_encodes_hand_and_table() {
+        let mut environment = Environment::new(3, Ruleset::default(), 4).unwrap();
+        let observation = environment.reset(9);
+        let game_runtime = &environment.game_runtime;
+
+        assert_eq!(observation.features.len(), OBSERVATION_LEN);
+        assert_eq!(
+            observation.features[..NUM_CARD_TYPES].iter().sum::<f32>(),
+            7.0
+        );
+
+        let top_card = game_runtime.game_state().last_played_card;
+        assert_eq!(
+            observation.features[NUM_CARD_TYPES + card_index(top_card)],
+            1.0
+        );
+
+        let opponent_sizes = &observation.features[2 * NUM_CARD_TYPES + 4..][..MAX_PLAYERS - 1];
+        assert_eq!(opponent_sizes[..3], [7.0, 7.0, 0.0]);
+    }
+
+    #[test]
+    fn reset_with_the_same_seed_deals_the_same_game() {
+        let mut environment = Environment::new(2, Ruleset::default(), 0).unwrap();
+
+        assert_eq!(environment.reset(3), environment.reset(3));
+        assert_ne!(environment.reset(3), environment.reset(4));
+    }
+
+    #[test]
+    fn step_rejects_illegal_action_indices() {
+        let mut environment = Environment::new(2, Ruleset::default(), 0).unwrap();
+
+        assert_eq!(
+            environment.step(NUM_ACTIONS),
+            Err(EnvError::InvalidActionIndex(NUM_ACTIONS))
+        );
+        assert_eq!(
+            environment.step(CALL_BLUFF_INDEX),
+            Err(EnvError::IllegalAction(CALL_BLUFF_INDEX))
+        );
+    }
+
+    #[test]
+    fn player_counts_without_room_are_rejected() {
+        let quick = Ruleset::by_name("quick").unwrap();
+        assert!(quick.max_players() > MAX_PLAYERS);
+
+        assert!(Environment::new(MAX_PLAYERS, quick, 0).is_ok());
+        for num_players in [0, 1, MAX_PLAYERS + 1, quick.max_players()] {
+            assert_eq!(
+                Environment::new(num_players, quick, 0).err(),
+                Some(EnvError::InvalidPlayerCount(num_players))
+            );
+        }
+    }
+
+    #[test]
+    fn self_play_runs_until_done() {
+        let mut environment = Environment::new(4, Ruleset::default(), 0).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut observation = environment.reset(1);
+
+        loop {
+            let legal_indices: Vec<usize> = (0..NUM_ACTIONS)
+                .filter(|&idx| observation.legal_mask[idx])
+                .collect();
+
+            let step = environment
+                .step(*legal_indices.choose(&mut rng).unwrap())
+                .unwrap();
+
+            if step.done {
+                assert_eq!(step.reward, 1.0);
+                assert!(step.rewards.iter().sum::<f32>().abs() < 1e-6);
+                break;
+            }
+
+            assert_eq!(step.rewards, vec![0.0; 4]);
+            observation = step.observation;
+        }
+
+        assert_eq!(environment.step(PASS_INDEX), Err(EnvError::GameIsOver));
+    }
+}
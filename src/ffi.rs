@@ -0,0 +1,393 @@
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use crate::env::{action_from_index, action_index, card_index};
+use crate::game_executor::{ActionError, GameRuntime};
+use crate::game_state::Direction;
+use crate::ruleset::Ruleset;
+use crate::view::PlayerView;
+
+// Cards and actions cross the boundary as their indices in the action space of `env`, which
+// `constants_match_the_action_space` keeps in sync with these.
+
+/// Numeric cards are `color * 10 + value`, followed by the two wild cards.
+pub const UNO_NUM_CARD_TYPES: u32 = 42;
+pub const UNO_CARD_WILD: u32 = 40;
+pub const UNO_CARD_DRAW_FOUR: u32 = 41;
+
+pub const UNO_COLOR_BLUE: i32 = 0;
+pub const UNO_COLOR_GREEN: i32 = 1;
+pub const UNO_COLOR_YELLOW: i32 = 2;
+pub const UNO_COLOR_RED: i32 = 3;
+
+/// Playing a card is the action with the card's index. Choosing a color adds the color to this.
+pub const UNO_ACTION_CHOOSE_COLOR: u32 = 42;
+pub const UNO_ACTION_DRAW_ONE: u32 = 46;
+pub const UNO_ACTION_DRAW_FOUR: u32 = 47;
+pub const UNO_ACTION_CALL_BLUFF: u32 = 48;
+pub const UNO_ACTION_PASS: u32 = 49;
+pub const UNO_NUM_ACTIONS: u32 = 50;
+
+/// Stands for a missing color, card or winner.
+pub const UNO_NONE: i32 = -1;
+
+/// A game in progress. Only ever handled through a pointer.
+pub struct UnoGame {
+    game_runtime: GameRuntime,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum UnoStatus {
+    Ok = 0,
+    NullPointer,
+    InvalidAction,
+    IllegalAction,
+    GameIsOver,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum UnoDirection {
+    Forward = 0,
+    Backward,
+}
+
+/// A snapshot of what one player can see. It owns its arrays, so it outlives the game it was
+/// taken from, and has to be released with `uno_player_view_free`.
+#[repr(C)]
+pub struct UnoPlayerView {
+    pub player_idx: usize,
+    pub current_player_idx: usize,
+    pub num_players: usize,
+    /// Card indices, `hand_len` of them.
+    pub hand: *const u8,
+    pub hand_len: usize,
+    /// How many cards each player holds, `num_players` of them.
+    pub hand_sizes: *const usize,
+    pub top_card: u32,
+    /// `UNO_NONE` while a wild card waits for its color.
+    pub active_color: i32,
+    /// The card this player just drew, or `UNO_NONE`.
+    pub drawn_card: i32,
+    pub drawing_deck_len: usize,
+    pub discard_pile_len: usize,
+    pub direction: UnoDirection,
+    /// `UNO_NONE` until somebody wins.
+    pub winner: i32,
+}
+
+/// Deals a game between `num_players` players, shuffled by `seed`. `ruleset` is one of the
+/// ruleset names, such as `"standard"` or `"quick"`, or null for the standard rules.
+///
+/// Returns null if the ruleset is unknown or the players can't be dealt a hand. The game has to
+/// be released with `uno_game_free`.
+///
+/// # Safety
+///
+/// `ruleset` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_new(
+    num_players: usize,
+    ruleset: *const c_char,
+    seed: u64,
+) -> *mut UnoGame {
+    let ruleset = if ruleset.is_null() {
+        Some(Ruleset::default())
+    } else {
+        CStr::from_ptr(ruleset)
+            .to_str()
+            .ok()
+            .and_then(Ruleset::by_name)
+    };
+
+    match ruleset {
+        Some(ruleset) if (2..=ruleset.max_players()).contains(&num_players) => {
+            Box::into_raw(Box::new(UnoGame {
+                game_runtime: GameRuntime::from_seed(num_players, ruleset, seed),
+            }))
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Copies a game, e.g. to try actions out on. Returns null if `game` is null.
+///
+/// # Safety
+///
+/// `game` must be null or a live game from `uno_game_new` or `uno_game_clone`.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_clone(game: *const UnoGame) -> *mut UnoGame {
+    match game.as_ref() {
+        Some(game) => Box::into_raw(Box::new(UnoGame {
+            game_runtime: game.game_runtime.clone(),
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `game` must be null or a live game, which is no longer usable afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_free(game: *mut UnoGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// # Safety
+///
+/// `game` must be null or a live game.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_num_players(game: *const UnoGame) -> usize {
+    game.as_ref()
+        .map_or(0, |game| game.game_runtime.num_players())
+}
+
+/// # Safety
+///
+/// `game` must be null or a live game.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_current_player(game: *const UnoGame) -> usize {
+    game.as_ref()
+        .map_or(0, |game| game.game_runtime.current_player_idx())
+}
+
+/// The winner's seat, or `UNO_NONE` while the game goes on.
+///
+/// # Safety
+///
+/// `game` must be null or a live game.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_winner(game: *const UnoGame) -> i32 {
+    game.as_ref()
+        .and_then(|game| game.game_runtime.winner())
+        .map_or(UNO_NONE, |player_idx| player_idx as i32)
+}
+
+/// Writes up to `capacity` of the current player's legal actions to `actions` and returns how
+/// many there are in total, so a call with a capacity of zero finds out how much room is needed.
+/// `UNO_NUM_ACTIONS` is always enough.
+///
+/// # Safety
+///
+/// `game` must be null or a live game, and `actions` must have room for `capacity` actions.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_legal_actions(
+    game: *const UnoGame,
+    actions: *mut u32,
+    capacity: usize,
+) -> usize {
+    let Some(game) = game.as_ref() else {
+        return 0;
+    };
+
+    let legal_actions = game.game_runtime.legal_actions();
+    for (i, &action) in legal_actions.iter().take(capacity).enumerate() {
+        // Players are only ever offered the draws that have an index.
+        *actions.add(i) = action_index(action).unwrap() as u32;
+    }
+
+    legal_actions.len()
+}
+
+/// Takes `action` for the current player. The game is left untouched unless this returns
+/// `UNO_STATUS_OK`.
+///
+/// # Safety
+///
+/// `game` must be null or a live game.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_apply_action(game: *mut UnoGame, action: u32) -> UnoStatus {
+    let Some(game) = game.as_mut() else {
+        return UnoStatus::NullPointer;
+    };
+    let Some(action) = action_from_index(action as usize) else {
+        return UnoStatus::InvalidAction;
+    };
+
+    match game.game_runtime.execute_action(action) {
+        Ok(()) => UnoStatus::Ok,
+        Err(ActionError::GameIsOver) => UnoStatus::GameIsOver,
+        Err(ActionError::IllegalAction(_)) => UnoStatus::IllegalAction,
+    }
+}
+
+/// What `player_idx` can see right now, or null if there is no such player. The view has to be
+/// released with `uno_player_view_free`.
+///
+/// # Safety
+///
+/// `game` must be null or a live game.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_player_view(
+    game: *const UnoGame,
+    player_idx: usize,
+) -> *mut UnoPlayerView {
+    let Some(game) = game.as_ref() else {
+        return ptr::null_mut();
+    };
+    if player_idx >= game.game_runtime.num_players() {
+        return ptr::null_mut();
+    }
+
+    let view = PlayerView::new(&game.game_runtime, player_idx);
+    let hand: Box<[u8]> = view
+        .hand
+        .iter()
+        .map(|&card| card_index(card) as u8)
+        .collect();
+    let hand_sizes: Box<[usize]> = view.table.hand_sizes.clone().into_boxed_slice();
+
+    Box::into_raw(Box::new(UnoPlayerView {
+        player_idx,
+        current_player_idx: view.table.current_player_idx,
+        num_players: hand_sizes.len(),
+        hand_len: hand.len(),
+        hand: Box::into_raw(hand) as *const u8,
+        hand_sizes: Box::into_raw(hand_sizes) as *const usize,
+        top_card: card_index(view.table.top_card()) as u32,
+        active_color: view
+            .table
+            .active_color
+            .map_or(UNO_NONE, |color| color as i32),
+        drawn_card: view
+            .drawn_card()
+            .map_or(UNO_NONE, |card| card_index(card) as i32),
+        drawing_deck_len: view.table.drawing_deck_len,
+        discard_pile_len: view.table.discard_pile.len(),
+        direction: match view.table.direction() {
+            Direction::Forward => UnoDirection::Forward,
+            Direction::Backward => UnoDirection::Backward,
+        },
+        winner: view
+            .table
+            .winner
+            .map_or(UNO_NONE, |player_idx| player_idx as i32),
+    }))
+}
+
+/// # Safety
+///
+/// `view` must be null or a view from `uno_game_player_view` that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn uno_player_view_free(view: *mut UnoPlayerView) {
+    if view.is_null() {
+        return;
+    }
+
+    let view = Box::from_raw(view);
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+        view.hand as *mut u8,
+        view.hand_len,
+    )));
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+        view.hand_sizes as *mut usize,
+        view.num_players,
+    )));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+    use crate::env::{card_from_index, NUM_ACTIONS, NUM_CARD_TYPES};
+    use crate::game_state::{Action, Card, CardColor, DrawAmount, WildCard, WildCardKind};
+
+    #[test]
+    fn constants_match_the_action_space() {
+        assert_eq!(UNO_NUM_CARD_TYPES as usize, NUM_CARD_TYPES);
+        assert_eq!(UNO_NUM_ACTIONS as usize, NUM_ACTIONS);
+        assert_eq!(
+            card_from_index(UNO_CARD_WILD as usize),
+            Some(Card::Special(WildCard {
+                kind: WildCardKind::Colorchooser
+            }))
+        );
+        assert_eq!(
+            card_from_index(UNO_CARD_DRAW_FOUR as usize),
+            Some(Card::Special(WildCard {
+                kind: WildCardKind::DrawFour
+            }))
+        );
+
+        for (color, constant) in CardColor::ALL.into_iter().zip([
+            UNO_COLOR_BLUE,
+            UNO_COLOR_GREEN,
+            UNO_COLOR_YELLOW,
+            UNO_COLOR_RED,
+        ]) {
+            assert_eq!(color as i32, constant);
+            assert_eq!(
+                action_index(Action::ChooseColor { color }),
+                Some((UNO_ACTION_CHOOSE_COLOR as i32 + constant) as usize)
+            );
+        }
+
+        for (action, constant) in [
+            (
+                Action::DrawCard {
+                    amount: DrawAmount::One,
+                },
+                UNO_ACTION_DRAW_ONE,
+            ),
+            (
+                Action::DrawCard {
+                    amount: DrawAmount::Four,
+                },
+                UNO_ACTION_DRAW_FOUR,
+            ),
+            (Action::CallBluff, UNO_ACTION_CALL_BLUFF),
+            (Action::Pass, UNO_ACTION_PASS),
+        ] {
+            assert_eq!(action_index(action), Some(constant as usize));
+        }
+    }
+
+    #[test]
+    fn games_that_cant_be_dealt_are_null() {
+        let unknown = CString::new("calvinball").unwrap();
+
+        unsafe {
+            assert!(uno_game_new(1, ptr::null(), 0).is_null());
+            assert!(uno_game_new(11, ptr::null(), 0).is_null());
+            assert!(uno_game_new(2, unknown.as_ptr(), 0).is_null());
+            assert!(uno_game_player_view(ptr::null(), 0).is_null());
+            assert_eq!(
+                uno_game_apply_action(ptr::null_mut(), UNO_ACTION_PASS),
+                UnoStatus::NullPointer
+            );
+        }
+    }
+
+    #[test]
+    fn views_match_the_runtime() {
+        unsafe {
+            let game = uno_game_new(3, ptr::null(), 7);
+            let game_runtime = &(*game).game_runtime;
+            let view = uno_game_player_view(game, 1);
+
+            let hand = std::slice::from_raw_parts((*view).hand, (*view).hand_len);
+            let expected: Vec<u8> = game_runtime
+                .hand(1)
+                .iter()
+                .map(|&card| card_index(card) as u8)
+                .collect();
+            assert_eq!(hand, &expected[..]);
+            assert_eq!(
+                std::slice::from_raw_parts((*view).hand_sizes, (*view).num_players),
+                &[7, 7, 7]
+            );
+            assert_eq!(
+                (*view).top_card as usize,
+                card_index(game_runtime.top_card())
+            );
+            assert_eq!((*view).winner, UNO_NONE);
+
+            uno_player_view_free(view);
+            uno_game_free(game);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::agent::Agent;
+use crate::bots;
+use crate::env::{self, NUM_ACTIONS};
+use crate::game_executor::GameRuntime;
+use crate::game_state::{Action, Card, Deck, DrawAmount};
+use crate::notation;
+use crate::packed::{PackedCard, NUM_CARD_TYPES};
+use crate::perft::Position;
+use crate::protocol::ClientMessage;
+use crate::ruleset::Ruleset;
+use crate::view::PlayerView;
+
+/// Bytes with this bit set pick among the legal actions, so that inputs get past the first few
+/// moves. The others are taken as action indices, legal or not.
+const LEGAL_ACTION_BIT: u8 = 0x80;
+
+/// The number of players, the ruleset and the seed.
+const HEADER_LEN: usize = 10;
+
+fn decode_game(data: &[u8]) -> (GameRuntime, &[u8]) {
+    let mut header = [0; HEADER_LEN];
+    let header_len = data.len().min(HEADER_LEN);
+    header[..header_len].copy_from_slice(&data[..header_len]);
+
+    let ruleset = Ruleset::by_name(Ruleset::NAMES[header[1] as usize % Ruleset::NAMES.len()])
+        .unwrap_or_default();
+    let num_players = 2 + header[0] as usize % (ruleset.max_players() - 1);
+    let seed = u64::from_le_bytes(header[2..].try_into().unwrap_or_default());
+
+    let game_runtime = GameRuntime::new(num_players, ruleset, StdRng::seed_from_u64(seed));
+    (game_runtime, &data[header_len..])
+}
+
+fn decode_action(byte: u8, legal_actions: &[Action]) -> Option<Action> {
+    if byte & LEGAL_ACTION_BIT != 0 {
+        let idx = (byte & !LEGAL_ACTION_BIT) as usize;
+        return legal_actions.get(idx % legal_actions.len().max(1)).copied();
+    }
+
+    match byte as usize {
+        idx if idx < NUM_ACTIONS => env::action_from_index(idx),
+        // Draws nobody can choose, which the action indices leave out.
+        idx if idx == NUM_ACTIONS => Some(Action::DrawCard {
+            amount: DrawAmount::Two,
+        }),
+        _ => Some(Action::DrawCard {
+            amount: DrawAmount::Six,
+        }),
+    }
+}
+
+fn encode_action(action: Action, legal_actions: &[Action]) -> u8 {
+    match legal_actions.iter().position(|&legal| legal == action) {
+        Some(idx) if idx < LEGAL_ACTION_BIT as usize => LEGAL_ACTION_BIT | idx as u8,
+        _ => env::action_index(action).map_or(0, |idx| idx as u8),
+    }
+}
+
+/// Plays the actions encoded in `data`, legal or not, checking that the game never gets into a
+/// state it shouldn't. Panics only on a bug.
+pub fn run_actions(data: &[u8]) {
+    let (mut game_runtime, actions) = decode_game(data);
+    let num_players = game_runtime.num_players();
+    let deck_counts = card_counts(&Deck::standard().cards);
+    let mut legal_actions = vec![];
+
+    for &byte in actions {
+        game_runtime.legal_actions_into(&mut legal_actions);
+        assert_eq!(legal_actions.is_empty(), game_runtime.winner().is_some());
+
+        let Some(action) = decode_action(byte, &legal_actions) else {
+            continue;
+        };
+
+        let result = game_runtime.execute_action(action);
+        assert_eq!(result.is_ok(), legal_actions.contains(&action));
+
+        // Every card of the deck is somewhere, exactly once.
+        let mut counts = card_counts(game_runtime.drawing_deck());
+        for (count, discarded) in counts
+            .iter_mut()
+            .zip(card_counts(game_runtime.discard_pile()))
+        {
+            *count += discarded;
+        }
+        for player_idx in 0..num_players {
+            for (count, held) in counts
+                .iter_mut()
+                .zip(card_counts(&game_runtime.hand(player_idx)))
+            {
+                *count += held;
+            }
+        }
+        assert_eq!(counts, deck_counts);
+
+        assert!(game_runtime.current_player_idx() < num_players);
+        assert!(game_runtime
+            .winner()
+            .is_none_or(|winner| winner < num_players));
+    }
+}
+
+fn card_counts(cards: &[Card]) -> [usize; NUM_CARD_TYPES] {
+    let mut counts = [0; NUM_CARD_TYPES];
+    for &card in cards {
+        counts[PackedCard::new(card).index()] += 1;
+    }
+    counts
+}
+
+/// Feeds `text` to every parser, none of which may panic however malformed it is. Whatever
+/// parses has to read back the same once formatted.
+pub fn parse_text(text: &str) {
+    for word in text.split_whitespace() {
+        if let Some(action) = notation::parse_action(word) {
+            assert_eq!(
+                notation::parse_action(&notation::format_action(action)),
+                Some(action)
+            );
+        }
+    }
+
+    if let Ok(position) = Position::parse(text) {
+        position.perft(2);
+    }
+
+    let _ = serde_json::from_str::<ClientMessage>(text);
+}
+
+/// A game between heuristic bots, encoded for `run_actions`, to seed the fuzzer's corpus with.
+pub fn record_game(seed: u64) -> Vec<u8> {
+    let mut data = vec![seed as u8, (seed >> 8) as u8];
+    data.extend_from_slice(&seed.to_le_bytes());
+
+    let (mut game_runtime, _) = decode_game(&data);
+    let mut agents: Vec<Box<dyn Agent>> = (0..game_runtime.num_players())
+        .map(|seat| bots::bot_by_name("heuristic", seed + seat as u64).unwrap())
+        .collect();
+    let mut legal_actions = vec![];
+
+    while game_runtime.winner().is_none() {
+        let player_idx = game_runtime.current_player_idx();
+        game_runtime.legal_actions_into(&mut legal_actions);
+
+        let view = PlayerView::new(&game_runtime, player_idx);
+        let Ok(action) = agents[player_idx].choose_action(&view, &legal_actions) else {
+            break;
+        };
+
+        data.push(encode_action(action, &legal_actions));
+        let seen_events = game_runtime.events().len();
+        if game_runtime.execute_action(action).is_err() {
+            break;
+        }
+
+        for event in &game_runtime.events()[seen_events..] {
+            for agent in agents.iter_mut() {
+                agent.observe_event(event);
+            }
+        }
+    }
+
+    data
+}
@@ -1,45 +1,450 @@
-use crate::game_state::{Action, Card, GameState};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
 
-struct GameRuntime {
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::{
+    Action, Card, CardColor, Deck, Direction, DrawAmount, GameState, WildCard, WildCardKind,
+};
+use crate::packed::{self, PackedCard, PackedHand};
+use crate::ruleset::Ruleset;
+
+/// A game in progress: the hands, the piles, whose turn it is and everything that has happened.
+#[derive(Clone)]
+pub struct GameRuntime {
     current_player_idx: usize,
-    players: Vec<Player>,
+    /// Only the counts by card type are kept, so every rule from what can be played to who has
+    /// won is a few bit operations, and the cards are put in order only when somebody looks.
+    hands: Vec<PackedHand>,
     drawing_deck: Vec<Card>,
+    discard_pile: Vec<Card>,
     game_state: GameState,
+    last_draw_four_was_bluff: bool,
+    winner: Option<usize>,
+    events: Vec<Event>,
+    actions_executed: usize,
+    ruleset: Ruleset,
+    rng: StdRng,
+    /// Reused for checking each action's legality.
+    action_buffer: Vec<Action>,
 }
 
-struct Player {
-    deck: Vec<Card>,
+/// Something that happened at the table that every player gets to see.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    Played {
+        player_idx: usize,
+        card: Card,
+    },
+    ChoseColor {
+        player_idx: usize,
+        color: CardColor,
+    },
+    /// Fewer cards than asked for are drawn when both the drawing deck and the discard pile run
+    /// out, so this is the number of cards that actually changed hands.
+    Drew {
+        player_idx: usize,
+        amount: usize,
+    },
+    CalledBluff {
+        player_idx: usize,
+        succeeded: bool,
+    },
+    Passed {
+        player_idx: usize,
+    },
+    ReshuffledDiscardPile,
+    Won {
+        player_idx: usize,
+    },
 }
 
+/// Why `GameRuntime::execute_action` turned an action down.
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum ActionError {
+    GameIsOver,
+    IllegalAction(Action),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActionError::GameIsOver => write!(f, "the game is already over"),
+            ActionError::IllegalAction(action) => write!(f, "illegal action {:?}", action),
+        }
+    }
+}
+
+impl core::error::Error for ActionError {}
+
 impl GameRuntime {
-    fn execute_action(&mut self, action: Action) {
+    /// Deals a game the way `new` does, with the shuffle decided by `seed`.
+    ///
+    /// Panics if `num_players` is below two or above `ruleset.max_players()`.
+    pub fn from_seed(num_players: usize, ruleset: Ruleset, seed: u64) -> GameRuntime {
+        GameRuntime::new(num_players, ruleset, StdRng::seed_from_u64(seed))
+    }
+
+    /// Shuffles a standard deck, deals a hand to every player and flips the first numeric card
+    /// onto the discard pile.
+    pub(crate) fn new(num_players: usize, ruleset: Ruleset, mut rng: StdRng) -> GameRuntime {
+        assert!(num_players >= 2, "a game needs at least two players");
+        assert!(
+            num_players <= ruleset.max_players(),
+            "there aren't enough cards to deal {} hands",
+            num_players
+        );
+
+        let mut drawing_deck = Deck::standard().cards;
+        drawing_deck.shuffle(&mut rng);
+
+        let hands = (0..num_players)
+            .map(|_| {
+                let hand = drawing_deck.split_off(drawing_deck.len() - ruleset.initial_hand_size);
+                PackedHand::from_cards(&hand)
+            })
+            .collect();
+
+        // Wild cards can't start the game, so they are buried back into the drawing deck.
+        let first_card_idx = drawing_deck
+            .iter()
+            .rposition(|card| matches!(card, Card::Normal(_)))
+            .unwrap();
+        let first_card = drawing_deck.remove(first_card_idx);
+
+        GameRuntime {
+            current_player_idx: 0,
+            hands,
+            drawing_deck,
+            discard_pile: vec![first_card],
+            game_state: GameState::new(first_card),
+            last_draw_four_was_bluff: false,
+            winner: None,
+            events: vec![],
+            actions_executed: 0,
+            ruleset,
+            rng,
+            action_buffer: vec![],
+        }
+    }
+
+    /// Builds a runtime in the middle of a game, e.g. one whose hidden cards have been guessed
+    /// so that a bot can search it.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn from_parts(
+        ruleset: Ruleset,
+        game_state: GameState,
+        current_player_idx: usize,
+        hands: Vec<Vec<Card>>,
+        drawing_deck: Vec<Card>,
+        discard_pile: Vec<Card>,
+        last_draw_four_was_bluff: bool,
+        rng: StdRng,
+    ) -> GameRuntime {
+        GameRuntime {
+            current_player_idx,
+            hands: hands
+                .iter()
+                .map(|hand| PackedHand::from_cards(hand))
+                .collect(),
+            drawing_deck,
+            discard_pile,
+            game_state,
+            last_draw_four_was_bluff,
+            winner: None,
+            events: vec![],
+            actions_executed: 0,
+            ruleset,
+            rng,
+            action_buffer: vec![],
+        }
+    }
+
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+
+    pub fn current_player_idx(&self) -> usize {
+        self.current_player_idx
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.hands.len()
+    }
+
+    /// The cards in `player_idx`'s hand, sorted by type. Panics if there is no such player.
+    pub fn hand(&self, player_idx: usize) -> Vec<Card> {
+        self.hands[player_idx].cards()
+    }
+
+    pub(crate) fn packed_hand(&self, player_idx: usize) -> &PackedHand {
+        &self.hands[player_idx]
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        &self.game_state
+    }
+
+    pub fn drawing_deck_len(&self) -> usize {
+        self.drawing_deck.len()
+    }
+
+    /// From the bottom of the pile to its top.
+    pub fn discard_pile(&self) -> &[Card] {
+        &self.discard_pile
+    }
+
+    pub fn top_card(&self) -> Card {
+        self.game_state.last_played_card
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.game_state.direction
+    }
+
+    pub(crate) fn drawing_deck(&self) -> &[Card] {
+        &self.drawing_deck
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn actions_executed(&self) -> usize {
+        self.actions_executed
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// Every action the current player may take, or none once the game is over.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        self.legal_actions_into(&mut actions);
+        actions
+    }
+
+    /// Same as `legal_actions`, but replaces the contents of `actions` instead of allocating, for
+    /// callers that ask on every move.
+    pub fn legal_actions_into(&self, actions: &mut Vec<Action>) {
+        if self.winner.is_some() {
+            actions.clear();
+            return;
+        }
+
+        self.game_state
+            .actions_for_hand_into(&self.hands[self.current_player_idx], actions);
+
+        if !self.ruleset.bluff_challenges {
+            actions.retain(|&action| action != Action::CallBluff);
+        }
+
+        // Right after drawing, the drawn card is the only one that can be played.
+        if self.ruleset.forced_play_after_draw
+            && actions[0] == Action::Pass
+            && self.has_any_playable()
+        {
+            actions.remove(0);
+        }
+    }
+
+    /// Whether the current player can play a card right now, which is much cheaper to find out
+    /// than listing the legal actions.
+    pub fn has_any_playable(&self) -> bool {
+        self.winner.is_none()
+            && self
+                .game_state
+                .has_any_playable(&self.hands[self.current_player_idx])
+    }
+
+    /// Takes `action` for the current player, leaving the game untouched if it isn't legal.
+    pub fn execute_action(&mut self, action: Action) -> Result<(), ActionError> {
+        if self.winner.is_some() {
+            return Err(ActionError::GameIsOver);
+        }
+
+        let mut legal_actions = core::mem::take(&mut self.action_buffer);
+        self.legal_actions_into(&mut legal_actions);
+        let is_legal = legal_actions.contains(&action);
+        self.action_buffer = legal_actions;
+
+        // Cards are only offered from the hand, unless a game was put together inconsistently.
+        let in_hand = match action {
+            Action::Play { card } => {
+                self.hands[self.current_player_idx].count(PackedCard::new(card)) > 0
+            }
+            _ => true,
+        };
+
+        if !is_legal || !in_hand {
+            return Err(ActionError::IllegalAction(action));
+        }
+
         self.game_state.last_action = action;
+        self.game_state.last_drew_card = None;
+
+        let player_idx = self.current_player_idx;
+        self.actions_executed += 1;
 
         match action {
             Action::Play { card } => {
+                let active_color = self.active_color();
+                let hand = &mut self.hands[self.current_player_idx];
+                hand.remove(PackedCard::new(card));
+
+                if card
+                    == Card::Special(WildCard {
+                        kind: WildCardKind::DrawFour,
+                    })
+                {
+                    self.last_draw_four_was_bluff = active_color
+                        .is_some_and(|color| hand.present() & packed::color_mask(color) != 0);
+                }
+
                 self.game_state.last_played_card = card;
-                let card_idx = self.players[self.current_player_idx]
-                    .deck
-                    .iter()
-                    .position(|&c| c == card)
-                    .unwrap();
-                self.players[self.current_player_idx].deck.remove(card_idx);
+                self.game_state.chosen_color = None;
+                self.discard_pile.push(card);
+                self.events.push(Event::Played { player_idx, card });
+
+                if hand.is_empty() {
+                    self.winner = Some(player_idx);
+                    self.events.push(Event::Won { player_idx });
+                } else if let Card::Normal(_) = card {
+                    self.advance_turn();
+                }
+            }
+            Action::ChooseColor { color } => {
+                self.game_state.chosen_color = Some(color);
+                self.events.push(Event::ChoseColor { player_idx, color });
+                self.advance_turn();
+            }
+            Action::DrawCard { amount } => {
+                let drawn_cards = self.draw_cards(player_idx, amount);
+
+                if amount == DrawAmount::One {
+                    self.game_state.last_drew_card = drawn_cards.first().copied();
+                } else {
+                    self.advance_turn();
+                }
+            }
+            Action::CallBluff => {
+                self.events.push(Event::CalledBluff {
+                    player_idx,
+                    succeeded: self.last_draw_four_was_bluff,
+                });
+
+                if self.last_draw_four_was_bluff {
+                    let bluffer_idx = self.previous_player_idx();
+                    self.draw_cards(bluffer_idx, DrawAmount::Four);
+                } else {
+                    self.draw_cards(player_idx, DrawAmount::Six);
+                    self.game_state.last_action = Action::DrawCard {
+                        amount: DrawAmount::Six,
+                    };
+                    self.advance_turn();
+                }
+            }
+            Action::Pass => {
+                self.events.push(Event::Passed { player_idx });
+                self.advance_turn();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The color a card must match to be played, or `None` if a wild card is still waiting for
+    /// its color to be chosen.
+    pub fn active_color(&self) -> Option<CardColor> {
+        self.game_state
+            .last_played_card
+            .get_color()
+            .or(self.game_state.chosen_color)
+    }
+
+    fn draw_cards(&mut self, player_idx: usize, amount: DrawAmount) -> Vec<Card> {
+        let amount = amount.count();
+
+        let mut drawn_cards = Vec::with_capacity(amount);
+
+        for _ in 0..amount {
+            if self.drawing_deck.is_empty() {
+                self.reshuffle_discard_pile();
+            }
+
+            match self.drawing_deck.pop() {
+                Some(card) => drawn_cards.push(card),
+                None => break,
             }
-            _ => todo!(),
         }
 
-        self.current_player_idx += 1;
+        let hand = &mut self.hands[player_idx];
+        for &card in &drawn_cards {
+            hand.insert(PackedCard::new(card));
+        }
+        self.events.push(Event::Drew {
+            player_idx,
+            amount: drawn_cards.len(),
+        });
+
+        drawn_cards
+    }
+
+    /// Turns every card under the top of the discard pile into a new drawing deck.
+    fn reshuffle_discard_pile(&mut self) {
+        if self.discard_pile.len() < 2 {
+            return;
+        }
+
+        let top_card = self.discard_pile.pop();
+        self.drawing_deck.append(&mut self.discard_pile);
+        self.drawing_deck.shuffle(&mut self.rng);
+        self.discard_pile.extend(top_card);
+        self.events.push(Event::ReshuffledDiscardPile);
+    }
+
+    fn advance_turn(&mut self) {
+        self.current_player_idx = self.next_player_idx();
+    }
+
+    pub fn next_player_idx(&self) -> usize {
+        let num_players = self.hands.len();
+
+        match self.game_state.direction {
+            Direction::Forward => (self.current_player_idx + 1) % num_players,
+            Direction::Backward => (self.current_player_idx + num_players - 1) % num_players,
+        }
+    }
+
+    fn previous_player_idx(&self) -> usize {
+        let num_players = self.hands.len();
+
+        match self.game_state.direction {
+            Direction::Forward => (self.current_player_idx + num_players - 1) % num_players,
+            Direction::Backward => (self.current_player_idx + 1) % num_players,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
 
     use super::*;
     use crate::game_state::*;
+    use crate::packed::NUM_CARD_TYPES;
 
-    // TODO: Don't remove duplicated card from player's deck.
     #[test]
     fn play_normal_card() {
         let last_played_card = Card::Normal(NormalCard {
@@ -53,46 +458,54 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
-        let player1 = Player {
-            deck: vec![
-                Card::Normal(NormalCard {
-                    kind: CardKind::Numeric(Numeric::Zero),
-                    color: CardColor::Green,
-                }),
-                Card::Normal(NormalCard {
-                    kind: CardKind::Numeric(Numeric::Three),
-                    color: CardColor::Yellow,
-                }),
-            ],
-        };
+        let hand1 = PackedHand::from_cards(&[
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Zero),
+                color: CardColor::Green,
+            }),
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Three),
+                color: CardColor::Yellow,
+            }),
+        ]);
 
-        let player2 = Player {
-            deck: vec![
-                Card::Normal(NormalCard {
-                    kind: CardKind::Numeric(Numeric::One),
-                    color: CardColor::Blue,
-                }),
-                Card::Normal(NormalCard {
-                    kind: CardKind::Numeric(Numeric::Two),
-                    color: CardColor::Red,
-                }),
-            ],
-        };
+        let hand2 = PackedHand::from_cards(&[
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::One),
+                color: CardColor::Blue,
+            }),
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Two),
+                color: CardColor::Red,
+            }),
+        ]);
 
         let mut game_runtime = GameRuntime {
             current_player_idx: 0usize,
-            players: vec![player1, player2],
+            hands: vec![hand1, hand2],
             drawing_deck: vec![],
+            discard_pile: vec![last_played_card],
             game_state,
+            last_draw_four_was_bluff: false,
+            winner: None,
+            events: vec![],
+            actions_executed: 0,
+            ruleset: Ruleset::default(),
+            rng: StdRng::seed_from_u64(0),
+            action_buffer: vec![],
         };
 
-        let next_card = game_runtime.players[0].deck[0];
+        let next_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Zero),
+            color: CardColor::Green,
+        });
         let next_action = Action::Play { card: next_card };
 
-        game_runtime.execute_action(next_action);
+        game_runtime.execute_action(next_action).unwrap();
 
         assert_eq!(game_runtime.current_player_idx, 1usize);
         assert_eq!(
@@ -101,21 +514,22 @@ mod tests {
                 last_action: Action::Play { card: next_card },
                 last_played_card: next_card,
                 last_drew_card: None,
+                chosen_color: None,
                 direction: Direction::Forward,
             }
         );
 
         assert_eq!(
-            game_runtime.players[0].deck,
-            &[Card::Normal(NormalCard {
+            game_runtime.hand(0),
+            [Card::Normal(NormalCard {
                 kind: CardKind::Numeric(Numeric::Three),
                 color: CardColor::Yellow,
             })]
         );
 
         assert_eq!(
-            game_runtime.players[1].deck,
-            &[
+            game_runtime.hand(1),
+            [
                 Card::Normal(NormalCard {
                     kind: CardKind::Numeric(Numeric::One),
                     color: CardColor::Blue,
@@ -127,4 +541,435 @@ mod tests {
             ]
         );
     }
+
+    fn make_runtime(
+        game_state: GameState,
+        decks: Vec<Vec<Card>>,
+        drawing_deck: Vec<Card>,
+    ) -> GameRuntime {
+        GameRuntime {
+            current_player_idx: 0usize,
+            hands: decks
+                .iter()
+                .map(|deck| PackedHand::from_cards(deck))
+                .collect(),
+            drawing_deck,
+            discard_pile: vec![game_state.last_played_card],
+            game_state,
+            last_draw_four_was_bluff: false,
+            winner: None,
+            events: vec![],
+            actions_executed: 0,
+            ruleset: Ruleset::default(),
+            rng: StdRng::seed_from_u64(0),
+            action_buffer: vec![],
+        }
+    }
+
+    fn blue_zero_card() -> Card {
+        Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Zero),
+            color: CardColor::Blue,
+        })
+    }
+
+    fn start_of_game(last_played_card: Card) -> GameState {
+        GameState {
+            last_action: Action::Play {
+                card: last_played_card,
+            },
+            last_played_card,
+            last_drew_card: None,
+            chosen_color: None,
+            direction: Direction::Forward,
+        }
+    }
+
+    #[test]
+    fn new_game_deals_initial_hands() {
+        let game_runtime = GameRuntime::new(4, Ruleset::default(), StdRng::seed_from_u64(42));
+
+        assert_eq!(game_runtime.num_players(), 4);
+
+        for player_idx in 0..4 {
+            assert_eq!(game_runtime.hand(player_idx).len(), 7);
+        }
+
+        assert!(matches!(
+            game_runtime.game_state().last_played_card,
+            Card::Normal(_)
+        ));
+        assert_eq!(
+            game_runtime.drawing_deck_len(),
+            Deck::standard().cards.len() - 4 * 7 - 1
+        );
+    }
+
+    #[test]
+    fn reject_illegal_action() {
+        let red_one_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::One),
+            color: CardColor::Red,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![vec![red_one_card, red_one_card], vec![red_one_card]],
+            vec![],
+        );
+
+        assert_eq!(
+            game_runtime.execute_action(Action::Play { card: red_one_card }),
+            Err(ActionError::IllegalAction(Action::Play {
+                card: red_one_card
+            }))
+        );
+        assert_eq!(game_runtime.current_player_idx(), 0usize);
+    }
+
+    #[test]
+    fn play_colorchooser_keeps_turn_until_color_is_chosen() {
+        let colorchooser_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+
+        let red_one_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::One),
+            color: CardColor::Red,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![
+                vec![colorchooser_card, red_one_card],
+                vec![red_one_card, red_one_card],
+            ],
+            vec![],
+        );
+
+        game_runtime
+            .execute_action(Action::Play {
+                card: colorchooser_card,
+            })
+            .unwrap();
+
+        assert_eq!(game_runtime.current_player_idx(), 0usize);
+        assert_eq!(game_runtime.active_color(), None);
+
+        game_runtime
+            .execute_action(Action::ChooseColor {
+                color: CardColor::Red,
+            })
+            .unwrap();
+
+        assert_eq!(game_runtime.current_player_idx(), 1usize);
+        assert_eq!(game_runtime.active_color(), Some(CardColor::Red));
+        assert_eq!(
+            game_runtime.legal_actions(),
+            vec![
+                Action::DrawCard {
+                    amount: DrawAmount::One
+                },
+                Action::Play { card: red_one_card },
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_one_card_then_pass_to_next_player() {
+        let green_two_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Two),
+            color: CardColor::Green,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![vec![green_two_card], vec![green_two_card]],
+            vec![green_two_card],
+        );
+
+        game_runtime
+            .execute_action(Action::DrawCard {
+                amount: DrawAmount::One,
+            })
+            .unwrap();
+
+        assert_eq!(game_runtime.current_player_idx(), 0usize);
+        assert_eq!(
+            game_runtime.game_state().last_drew_card,
+            Some(green_two_card)
+        );
+        assert_eq!(game_runtime.legal_actions(), vec![Action::Pass]);
+
+        game_runtime.execute_action(Action::Pass).unwrap();
+
+        assert_eq!(game_runtime.current_player_idx(), 1usize);
+        assert_eq!(game_runtime.hand(0).len(), 2usize);
+    }
+
+    #[test]
+    fn draw_from_reshuffled_discard_pile_when_drawing_deck_is_empty() {
+        let green_two_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Two),
+            color: CardColor::Green,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![vec![green_two_card], vec![green_two_card]],
+            vec![],
+        );
+        game_runtime.discard_pile = vec![green_two_card, blue_zero_card()];
+
+        game_runtime
+            .execute_action(Action::DrawCard {
+                amount: DrawAmount::One,
+            })
+            .unwrap();
+
+        assert_eq!(
+            game_runtime.game_state().last_drew_card,
+            Some(green_two_card)
+        );
+        assert_eq!(game_runtime.discard_pile, vec![blue_zero_card()]);
+        assert_eq!(
+            game_runtime.events(),
+            &[
+                Event::ReshuffledDiscardPile,
+                Event::Drew {
+                    player_idx: 0,
+                    amount: 1
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn calling_bluff_on_illegal_draw_four_makes_bluffer_draw_four() {
+        let draw_four_card = Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![
+                vec![draw_four_card, blue_zero_card()],
+                vec![blue_zero_card()],
+            ],
+            vec![blue_zero_card(); 4],
+        );
+
+        game_runtime
+            .execute_action(Action::Play {
+                card: draw_four_card,
+            })
+            .unwrap();
+        game_runtime
+            .execute_action(Action::ChooseColor {
+                color: CardColor::Red,
+            })
+            .unwrap();
+        game_runtime.execute_action(Action::CallBluff).unwrap();
+
+        assert_eq!(game_runtime.current_player_idx(), 1usize);
+        assert_eq!(game_runtime.hand(0).len(), 5usize);
+        assert_eq!(game_runtime.hand(1).len(), 1usize);
+        assert_eq!(
+            game_runtime.events()[2..],
+            [
+                Event::CalledBluff {
+                    player_idx: 1,
+                    succeeded: true
+                },
+                Event::Drew {
+                    player_idx: 0,
+                    amount: 4
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn calling_bluff_on_legal_draw_four_draws_six_and_loses_turn() {
+        let draw_four_card = Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        });
+
+        let red_one_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::One),
+            color: CardColor::Red,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![vec![draw_four_card, red_one_card], vec![red_one_card]],
+            vec![blue_zero_card(); 6],
+        );
+
+        game_runtime
+            .execute_action(Action::Play {
+                card: draw_four_card,
+            })
+            .unwrap();
+        game_runtime
+            .execute_action(Action::ChooseColor {
+                color: CardColor::Red,
+            })
+            .unwrap();
+        game_runtime.execute_action(Action::CallBluff).unwrap();
+
+        assert_eq!(game_runtime.current_player_idx(), 0usize);
+        assert_eq!(
+            game_runtime.game_state().last_action,
+            Action::DrawCard {
+                amount: DrawAmount::Six
+            }
+        );
+        assert_eq!(game_runtime.hand(1).len(), 7usize);
+        assert_eq!(
+            game_runtime.legal_actions(),
+            vec![
+                Action::DrawCard {
+                    amount: DrawAmount::One
+                },
+                Action::Play { card: red_one_card },
+            ]
+        );
+    }
+
+    #[test]
+    fn playing_last_card_wins_the_game() {
+        let blue_one_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::One),
+            color: CardColor::Blue,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![vec![blue_one_card], vec![blue_one_card]],
+            vec![],
+        );
+
+        game_runtime
+            .execute_action(Action::Play {
+                card: blue_one_card,
+            })
+            .unwrap();
+
+        assert_eq!(game_runtime.winner(), Some(0usize));
+        assert_eq!(game_runtime.legal_actions(), vec![]);
+        assert_eq!(
+            game_runtime.execute_action(Action::Pass),
+            Err(ActionError::GameIsOver)
+        );
+    }
+
+    #[test]
+    fn has_any_playable_agrees_with_legal_actions() {
+        for seed in 0..20 {
+            let ruleset = Ruleset {
+                forced_play_after_draw: seed % 2 == 0,
+                ..Ruleset::default()
+            };
+            let mut game_runtime = GameRuntime::new(3, ruleset, StdRng::seed_from_u64(seed));
+            let mut legal_actions = vec![Action::CallBluff; 8];
+
+            while game_runtime.winner().is_none() {
+                game_runtime.legal_actions_into(&mut legal_actions);
+                assert_eq!(legal_actions, game_runtime.legal_actions());
+                assert_eq!(
+                    game_runtime.has_any_playable(),
+                    legal_actions
+                        .iter()
+                        .any(|action| matches!(action, Action::Play { .. }))
+                );
+
+                // Playing the last legal action plays a card whenever there is one, so games end.
+                game_runtime
+                    .execute_action(*legal_actions.last().unwrap())
+                    .unwrap();
+            }
+
+            assert!(!game_runtime.has_any_playable());
+        }
+    }
+
+    #[test]
+    fn playing_a_duplicated_card_removes_only_one_copy() {
+        let blue_one_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::One),
+            color: CardColor::Blue,
+        });
+
+        let mut game_runtime = make_runtime(
+            start_of_game(blue_zero_card()),
+            vec![vec![blue_one_card, blue_one_card], vec![blue_one_card]],
+            vec![],
+        );
+
+        game_runtime
+            .execute_action(Action::Play {
+                card: blue_one_card,
+            })
+            .unwrap();
+
+        assert_eq!(game_runtime.hand(0), vec![blue_one_card]);
+        assert_eq!(game_runtime.winner(), None);
+    }
+
+    fn card_counts(cards: impl IntoIterator<Item = Card>) -> [usize; NUM_CARD_TYPES] {
+        let mut counts = [0; NUM_CARD_TYPES];
+        for card in cards {
+            counts[PackedCard::new(card).index()] += 1;
+        }
+        counts
+    }
+
+    fn check_invariants(game_runtime: &GameRuntime, num_players: usize) {
+        let cards = (0..game_runtime.num_players())
+            .flat_map(|player_idx| game_runtime.hand(player_idx))
+            .chain(game_runtime.drawing_deck().iter().copied())
+            .chain(game_runtime.discard_pile().iter().copied());
+
+        // Every card of the deck is somewhere, exactly once.
+        assert_eq!(card_counts(cards), card_counts(Deck::standard().cards));
+        assert_eq!(game_runtime.num_players(), num_players);
+        assert!(game_runtime.current_player_idx() < num_players);
+        assert_eq!(
+            game_runtime.discard_pile().last(),
+            Some(&game_runtime.game_state().last_played_card)
+        );
+
+        let legal_actions = game_runtime.legal_actions();
+        assert_eq!(legal_actions.is_empty(), game_runtime.winner().is_some());
+
+        for &action in &legal_actions {
+            assert_eq!(game_runtime.clone().execute_action(action), Ok(()));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn random_games_keep_every_invariant(
+            num_players in 2usize..=6,
+            ruleset_idx in 0..Ruleset::NAMES.len(),
+            seed: u64,
+            choices in prop::collection::vec(any::<prop::sample::Index>(), 1..400),
+        ) {
+            let ruleset = Ruleset::by_name(Ruleset::NAMES[ruleset_idx]).unwrap();
+            let mut game_runtime =
+                GameRuntime::new(num_players, ruleset, StdRng::seed_from_u64(seed));
+            check_invariants(&game_runtime, num_players);
+
+            for choice in choices {
+                if game_runtime.winner().is_some() {
+                    break;
+                }
+
+                let action = *choice.get(&game_runtime.legal_actions());
+                game_runtime.execute_action(action).unwrap();
+                check_invariants(&game_runtime, num_players);
+            }
+        }
+    }
 }
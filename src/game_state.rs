@@ -1,17 +1,77 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::packed::{self, PackedCard, PackedHand, SetBits};
+
+/// Everything the rules look at to tell which actions are legal, without the hands and piles
+/// that `GameRuntime` keeps around it.
 #[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) struct GameState {
-    pub(crate) last_action: Action,
-    pub(crate) last_played_card: Card,
-    pub(crate) last_drew_card: Option<Card>,
-    pub(crate) direction: Direction,
+#[non_exhaustive]
+pub struct GameState {
+    pub last_action: Action,
+    pub last_played_card: Card,
+    pub last_drew_card: Option<Card>,
+    /// The color chosen for the wild card on top of the discard pile.
+    pub chosen_color: Option<CardColor>,
+    pub direction: Direction,
 }
 
-pub(crate) struct Deck {
+/// A pile of cards, such as a hand or the full deck a game is dealt from.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Deck {
     pub(crate) cards: Vec<Card>,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum Action {
+impl Deck {
+    pub fn new(cards: Vec<Card>) -> Deck {
+        Deck { cards }
+    }
+
+    /// Builds the full 84-card deck: one zero and two of every other numeric per color, plus
+    /// four of each wild card.
+    pub fn standard() -> Deck {
+        let mut cards = Vec::with_capacity(84);
+
+        for color in CardColor::ALL {
+            for kind in Numeric::ALL {
+                let copies = if kind == Numeric::Zero { 1 } else { 2 };
+
+                for _ in 0..copies {
+                    cards.push(Card::Normal(NormalCard {
+                        kind: CardKind::Numeric(kind),
+                        color,
+                    }));
+                }
+            }
+        }
+
+        for kind in [WildCardKind::DrawFour, WildCardKind::Colorchooser] {
+            for _ in 0..4 {
+                cards.push(Card::Special(WildCard { kind }));
+            }
+        }
+
+        Deck { cards }
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Action {
     Play { card: Card },
     ChooseColor { color: CardColor },
     DrawCard { amount: DrawAmount },
@@ -19,14 +79,16 @@ pub(crate) enum Action {
     Pass,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum Card {
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Card {
     Normal(NormalCard),
     Special(WildCard),
 }
 
 impl Card {
-    fn get_color(&self) -> Option<CardColor> {
+    /// The color printed on the card, which wild cards don't have.
+    pub fn get_color(&self) -> Option<CardColor> {
         match self {
             Card::Normal(card) => Some(card.color),
             _ => None,
@@ -34,24 +96,27 @@ impl Card {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) struct NormalCard {
-    pub(crate) kind: CardKind,
-    pub(crate) color: CardColor,
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct NormalCard {
+    pub kind: CardKind,
+    pub color: CardColor,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) struct WildCard {
-    pub(crate) kind: WildCardKind,
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct WildCard {
+    pub kind: WildCardKind,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum CardKind {
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum CardKind {
     Numeric(Numeric),
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum Numeric {
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Numeric {
     Zero,
     One,
     Two,
@@ -64,130 +129,208 @@ pub(crate) enum Numeric {
     Nine,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum WildCardKind {
+impl Numeric {
+    pub const ALL: [Numeric; 10] = [
+        Numeric::Zero,
+        Numeric::One,
+        Numeric::Two,
+        Numeric::Three,
+        Numeric::Four,
+        Numeric::Five,
+        Numeric::Six,
+        Numeric::Seven,
+        Numeric::Eight,
+        Numeric::Nine,
+    ];
+
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum WildCardKind {
     DrawFour,
     Colorchooser,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum CardColor {
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CardColor {
     Blue,
     Green,
     Yellow,
     Red,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum DrawAmount {
+impl CardColor {
+    pub const ALL: [CardColor; 4] = [
+        CardColor::Blue,
+        CardColor::Green,
+        CardColor::Yellow,
+        CardColor::Red,
+    ];
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DrawAmount {
     One,
+    // Reserved for the draw-two card, which isn't part of the deck yet.
     Two,
     Four,
     Six,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum Direction {
+impl DrawAmount {
+    pub fn count(self) -> usize {
+        match self {
+            DrawAmount::One => 1,
+            DrawAmount::Two => 2,
+            DrawAmount::Four => 4,
+            DrawAmount::Six => 6,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
     Forward,
+    // Reserved for the reverse card, which isn't part of the deck yet.
     Backward,
 }
 
+const CHOOSE_COLOR_ACTIONS: [Action; 4] = [
+    Action::ChooseColor {
+        color: CardColor::Blue,
+    },
+    Action::ChooseColor {
+        color: CardColor::Green,
+    },
+    Action::ChooseColor {
+        color: CardColor::Yellow,
+    },
+    Action::ChooseColor {
+        color: CardColor::Red,
+    },
+];
+
+/// One play per card type, however many cards of it the hand holds.
+fn push_playable_cards(hand: &PackedHand, playable: u64, actions: &mut Vec<Action>) {
+    actions.extend(SetBits(hand.present() & playable).map(|card| Action::Play {
+        card: card.unpack(),
+    }));
+}
+
 impl GameState {
-    fn get_actions_for_deck(&self, deck: &Deck) -> Vec<Action> {
+    /// A game that just started with `first_card` turned over.
+    pub fn new(first_card: Card) -> GameState {
+        GameState {
+            last_action: Action::Play { card: first_card },
+            last_played_card: first_card,
+            last_drew_card: None,
+            chosen_color: None,
+            direction: Direction::Forward,
+        }
+    }
+
+    /// Every action that may be taken with `deck` as the hand, with one play per card type.
+    pub fn get_actions_for_deck(&self, deck: &Deck) -> Vec<Action> {
+        let mut actions = Vec::new();
+        self.actions_for_deck_into(deck, &mut actions);
+        actions
+    }
+
+    /// Same as `get_actions_for_deck`, but replaces the contents of `actions` instead of
+    /// allocating, so that a buffer can be reused across calls.
+    pub fn actions_for_deck_into(&self, deck: &Deck, actions: &mut Vec<Action>) {
+        self.actions_for_hand_into(&PackedHand::from_cards(&deck.cards), actions);
+    }
+
+    pub(crate) fn actions_for_hand_into(&self, hand: &PackedHand, actions: &mut Vec<Action>) {
+        actions.clear();
+
         match self.last_action {
             Action::Play {
                 card:
                     Card::Special(WildCard {
                         kind: WildCardKind::Colorchooser | WildCardKind::DrawFour,
                     }),
-            } => vec![
-                Action::ChooseColor {
-                    color: CardColor::Blue,
-                },
-                Action::ChooseColor {
-                    color: CardColor::Green,
-                },
-                Action::ChooseColor {
-                    color: CardColor::Yellow,
-                },
-                Action::ChooseColor {
-                    color: CardColor::Red,
-                },
-            ],
-            Action::ChooseColor {
-                color: chosen_color,
-            } => match self.last_played_card {
-                Card::Special(WildCard {
-                    kind: WildCardKind::Colorchooser,
-                }) => std::iter::once(Action::DrawCard {
-                    amount: DrawAmount::One,
-                })
-                .chain(
-                    deck.cards
-                        .iter()
-                        .filter(|card| {
-                            card.get_color() == Some(chosen_color) || card.get_color().is_none()
-                        })
-                        .map(|&card| Action::Play { card }),
-                )
-                .collect(),
-                Card::Special(WildCard {
-                    kind: WildCardKind::DrawFour,
-                }) => vec![
+            } => actions.extend_from_slice(&CHOOSE_COLOR_ACTIONS),
+            Action::ChooseColor { .. }
+                if self.last_played_card
+                    == Card::Special(WildCard {
+                        kind: WildCardKind::DrawFour,
+                    }) =>
+            {
+                actions.extend_from_slice(&[
                     Action::DrawCard {
                         amount: DrawAmount::Four,
                     },
                     Action::CallBluff,
-                ],
-                _ => unreachable!("cannot have choose-color action after playing a normal card"),
-            },
-            Action::Play { .. } => {
-                let mut actions = vec![Action::DrawCard {
-                    amount: DrawAmount::One,
-                }];
-
-                for &card in &deck.cards {
-                    if self.can_play_card(card) {
-                        actions.push(Action::Play { card });
-                    }
-                }
-
-                actions
+                ])
             }
             Action::DrawCard {
                 amount: DrawAmount::One,
             } => {
-                let mut actions = vec![Action::Pass];
-                let last_drew_card = self.last_drew_card.unwrap();
+                actions.push(Action::Pass);
 
-                if self.can_play_card(last_drew_card) {
-                    actions.push(Action::Play {
-                        card: last_drew_card,
-                    });
+                match self.last_drew_card {
+                    Some(last_drew_card) if self.can_play_card(last_drew_card) => {
+                        actions.push(Action::Play {
+                            card: last_drew_card,
+                        });
+                    }
+                    _ => {}
                 }
-
-                actions
             }
-            _ => todo!(),
+            Action::Play { .. }
+            | Action::ChooseColor { .. }
+            | Action::DrawCard { .. }
+            | Action::CallBluff
+            | Action::Pass => {
+                actions.push(Action::DrawCard {
+                    amount: DrawAmount::One,
+                });
+                push_playable_cards(hand, self.playable_mask(), actions);
+            }
         }
     }
 
-    fn can_play_card(&self, card_to_play: Card) -> bool {
-        match (self.last_played_card, card_to_play) {
-            (Card::Normal(played), Card::Normal(to_play)) => {
-                played.kind == to_play.kind || played.color == to_play.color
+    /// Whether any card of `hand` may be played right now, without listing the actions. Right
+    /// after drawing, only the drawn card counts.
+    pub(crate) fn has_any_playable(&self, hand: &PackedHand) -> bool {
+        match self.last_action {
+            Action::DrawCard {
+                amount: DrawAmount::One,
+            } => self
+                .last_drew_card
+                .is_some_and(|card| self.can_play_card(card)),
+            // A draw four has to be answered by drawing or calling the bluff.
+            Action::ChooseColor { .. }
+                if self.last_played_card
+                    == Card::Special(WildCard {
+                        kind: WildCardKind::DrawFour,
+                    }) =>
+            {
+                false
             }
-            (Card::Normal(_), Card::Special(_)) => true,
-            (Card::Special(_), Card::Normal(to_play)) => match self.last_action {
-                Action::ChooseColor { color } => to_play.color == color,
-                _ => false,
-            },
-            (Card::Special(played), Card::Special(_)) => match self.last_action {
-                Action::ChooseColor { .. } => played.kind != WildCardKind::DrawFour,
-                _ => false,
-            },
+            _ => self.playable_mask() & hand.present() != 0,
         }
     }
+
+    pub fn can_play_card(&self, card_to_play: Card) -> bool {
+        self.playable_mask() & PackedCard::new(card_to_play).bit() != 0
+    }
+
+    /// The set of card types that may be played next, as bits indexed by `PackedCard`.
+    pub(crate) fn playable_mask(&self) -> u64 {
+        packed::playable_mask(self.last_played_card, self.chosen_color)
+    }
 }
 
 #[cfg(test)]
@@ -207,6 +350,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -252,6 +396,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -283,16 +428,16 @@ mod tests {
                     amount: DrawAmount::One
                 },
                 Action::Play {
-                    card: deck.cards[0]
+                    card: deck.cards[2]
                 },
                 Action::Play {
-                    card: deck.cards[1]
+                    card: deck.cards[0]
                 },
                 Action::Play {
-                    card: deck.cards[2]
+                    card: deck.cards[3]
                 },
                 Action::Play {
-                    card: deck.cards[3]
+                    card: deck.cards[1]
                 }
             ]
         );
@@ -311,6 +456,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -358,6 +504,7 @@ mod tests {
                 },
                 last_played_card,
                 last_drew_card: None,
+                chosen_color: None,
                 direction: Direction::Forward,
             };
 
@@ -379,10 +526,10 @@ mod tests {
                         amount: DrawAmount::One
                     },
                     Action::Play {
-                        card: deck.cards[0]
+                        card: deck.cards[1]
                     },
                     Action::Play {
-                        card: deck.cards[1]
+                        card: deck.cards[0]
                     }
                 ]
             );
@@ -414,6 +561,7 @@ mod tests {
                 },
                 last_played_card,
                 last_drew_card: None,
+                chosen_color: None,
                 direction: Direction::Forward,
             };
 
@@ -435,10 +583,10 @@ mod tests {
                         amount: DrawAmount::One
                     },
                     Action::Play {
-                        card: deck.cards[0]
+                        card: deck.cards[1]
                     },
                     Action::Play {
-                        card: deck.cards[1]
+                        card: deck.cards[0]
                     }
                 ]
             );
@@ -457,6 +605,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -507,6 +656,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -557,6 +707,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: Some(CardColor::Red),
             direction: Direction::Forward,
         };
 
@@ -599,6 +750,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: Some(CardColor::Red),
             direction: Direction::Forward,
         };
 
@@ -642,6 +794,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: None,
+            chosen_color: Some(CardColor::Red),
             direction: Direction::Forward,
         };
 
@@ -701,6 +854,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: Some(last_drew_card),
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -746,6 +900,7 @@ mod tests {
             },
             last_played_card,
             last_drew_card: Some(last_drew_card),
+            chosen_color: None,
             direction: Direction::Forward,
         };
 
@@ -764,4 +919,90 @@ mod tests {
 
         assert_eq!(game_state.get_actions_for_deck(&deck), vec![Action::Pass]);
     }
+
+    #[test]
+    fn test_can_play_card_of_chosen_color_after_previous_player_passed() {
+        let last_played_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+
+        let game_state = GameState {
+            last_action: Action::Pass,
+            last_played_card,
+            last_drew_card: None,
+            chosen_color: Some(CardColor::Green),
+            direction: Direction::Forward,
+        };
+
+        let green_five_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Five),
+            color: CardColor::Green,
+        });
+
+        let red_five_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Five),
+            color: CardColor::Red,
+        });
+
+        let deck = Deck {
+            cards: vec![green_five_card, red_five_card, last_played_card],
+        };
+
+        assert_eq!(
+            game_state.get_actions_for_deck(&deck),
+            vec![
+                Action::DrawCard {
+                    amount: DrawAmount::One
+                },
+                Action::Play {
+                    card: green_five_card
+                },
+                Action::Play {
+                    card: last_played_card
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_chosen_color_can_be_played_on_draw_four() {
+        let last_played_card = Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        });
+
+        let game_state = GameState {
+            last_action: Action::DrawCard {
+                amount: DrawAmount::Four,
+            },
+            last_played_card,
+            last_drew_card: None,
+            chosen_color: Some(CardColor::Green),
+            direction: Direction::Forward,
+        };
+
+        let green_five_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Five),
+            color: CardColor::Green,
+        });
+
+        let wild_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+
+        let deck = Deck {
+            cards: vec![green_five_card, wild_card, last_played_card],
+        };
+
+        assert_eq!(
+            game_state.get_actions_for_deck(&deck),
+            vec![
+                Action::DrawCard {
+                    amount: DrawAmount::One
+                },
+                Action::Play {
+                    card: green_five_card
+                },
+            ]
+        );
+    }
 }
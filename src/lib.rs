@@ -0,0 +1,70 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod agent;
+#[cfg(feature = "std")]
+mod bots;
+// Only public for the binaries, and no part of the library's API.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod cli;
+#[cfg(feature = "std")]
+mod engine;
+#[cfg(feature = "std")]
+mod env;
+#[cfg(feature = "std")]
+pub mod ffi;
+// Only public for the fuzz targets.
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+mod game_executor;
+mod game_state;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod lobby;
+#[cfg(feature = "std")]
+mod mcts;
+#[cfg(feature = "std")]
+mod notation;
+mod packed;
+#[cfg(feature = "std")]
+mod perft;
+// Only the views are of use without the server.
+#[cfg(feature = "std")]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+mod protocol;
+mod ruleset;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod server;
+#[cfg(feature = "std")]
+mod simulator;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod timer;
+#[cfg(feature = "std")]
+mod tournament;
+mod tracker;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod tui;
+mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod web;
+
+#[cfg(feature = "std")]
+pub use crate::env::{
+    EnvError, Environment, Observation, Step, MAX_PLAYERS, NUM_ACTIONS, NUM_CARD_TYPES,
+    OBSERVATION_LEN,
+};
+pub use crate::game_executor::{ActionError, Event, GameRuntime};
+pub use crate::game_state::{
+    Action, Card, CardColor, CardKind, Deck, Direction, DrawAmount, GameState, NormalCard, Numeric,
+    WildCard, WildCardKind,
+};
+#[cfg(feature = "std")]
+pub use crate::perft::{Position, PositionError};
+pub use crate::ruleset::Ruleset;
+pub use crate::tracker::CardTracker;
+pub use crate::view::{OmniscientView, PlayerView, SpectatorView};
@@ -0,0 +1,1823 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::agent::{self, Agent, AgentError, GameError};
+use crate::bots;
+use crate::game_executor::{Event, GameRuntime};
+use crate::game_state::Action;
+use crate::protocol::{
+    ClientMessage, PlayerScore, RoomSummary, SeatSummary, ServerMessage, SpectatorTableView,
+    TableView,
+};
+use crate::ruleset::Ruleset;
+use crate::server::{ConnectionId, Outboxes, ServerConfig};
+use crate::timer::{Clock, TurnTimer};
+use crate::view::{OmniscientView, PlayerView, SpectatorView};
+
+pub(crate) type RoomId = usize;
+
+/// More players than this wouldn't leave enough cards to draw from.
+const MAX_SEATS: usize = 10;
+
+/// The bots that can be seated in rooms. They take their turns on the thread that serves every
+/// room, so searching bots like `mcts` would hold up the whole server, and external engines would
+/// let players over the network start programs on it.
+pub const LOBBY_BOTS: [&str; 3] = ["random", "greedy", "heuristic"];
+
+enum Seat {
+    Empty,
+    Human {
+        connection_id: ConnectionId,
+        name: String,
+        ready: bool,
+        /// Lets the player take the seat back from another connection.
+        token: String,
+    },
+    /// A player who dropped out of a game in progress. The seat is kept for them until the grace
+    /// period runs out.
+    Away {
+        name: String,
+        token: String,
+        since: Instant,
+        /// How many of the game's events the player had been sent.
+        events_seen: usize,
+    },
+    Bot {
+        name: String,
+        /// Which bot takes the seat's turns, from `LOBBY_BOTS`.
+        bot: &'static str,
+    },
+}
+
+impl Seat {
+    fn connection_id(&self) -> Option<ConnectionId> {
+        match self {
+            Seat::Human { connection_id, .. } => Some(*connection_id),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        match self {
+            Seat::Empty => None,
+            Seat::Human { name, .. } | Seat::Away { name, .. } | Seat::Bot { name, .. } => {
+                Some(name)
+            }
+        }
+    }
+
+    fn token(&self) -> Option<&str> {
+        match self {
+            Seat::Human { token, .. } | Seat::Away { token, .. } => Some(token),
+            _ => None,
+        }
+    }
+
+    fn summary(&self) -> Option<SeatSummary> {
+        match self {
+            Seat::Empty => None,
+            Seat::Human { name, ready, .. } => Some(SeatSummary {
+                name: name.clone(),
+                bot: false,
+                ready: *ready,
+                away: false,
+            }),
+            Seat::Away { name, .. } => Some(SeatSummary {
+                name: name.clone(),
+                bot: false,
+                ready: true,
+                away: true,
+            }),
+            Seat::Bot { name, .. } => Some(SeatSummary {
+                name: name.clone(),
+                bot: true,
+                ready: true,
+                away: false,
+            }),
+        }
+    }
+}
+
+/// A player at the other end of a connection. The room passes on whatever they send, so the
+/// player only has an action once one came in.
+struct NetworkAgent {
+    actions: Receiver<Action>,
+}
+
+impl Agent for NetworkAgent {
+    fn choose_action(
+        &mut self,
+        _view: &PlayerView,
+        _legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        match self.actions.try_recv() {
+            Ok(action) => Ok(action),
+            Err(TryRecvError::Empty) => Err(AgentError::Waiting),
+            Err(TryRecvError::Disconnected) => Err(AgentError::Left),
+        }
+    }
+}
+
+/// How far the game got when the room asked the current player for an action.
+enum Step {
+    Waiting,
+    Played,
+    GameOver(GameResult),
+}
+
+/// Somebody watching a room without a seat. They never get to act.
+struct Spectator {
+    connection_id: ConnectionId,
+    /// Whether they see every hand. Those who do only see the game after a delay, so they can't
+    /// tip off the players.
+    omniscient: bool,
+}
+
+/// How a finished game went, for the lobby to keep score.
+struct GameResult {
+    names: Vec<String>,
+    winner: usize,
+    points: u32,
+}
+
+/// A table with its own game. Bots take their turns as soon as it's their turn.
+struct Room {
+    ruleset_name: String,
+    ruleset: Ruleset,
+    seats: Vec<Seat>,
+    game_runtime: Option<GameRuntime>,
+    /// Who makes the moves of each seat during a game: a bot, or a `NetworkAgent` for players
+    /// who sat down themselves.
+    agents: Vec<Box<dyn Agent>>,
+    /// Where the actions of players who sat down themselves go, by seat.
+    actions: Vec<Option<Sender<Action>>>,
+    clock: Arc<dyn Clock>,
+    turn_started_at: Instant,
+    /// How many turns in a row each seat has timed out.
+    timeouts: Vec<usize>,
+    spectators: Vec<Spectator>,
+    /// What omniscient spectators are going to be sent, with when it happened.
+    delayed: VecDeque<(Instant, ServerMessage)>,
+}
+
+impl Room {
+    fn summary(&self, room_id: RoomId) -> RoomSummary {
+        RoomSummary {
+            room_id,
+            ruleset: self.ruleset_name.clone(),
+            seats: self.seats.iter().map(Seat::summary).collect(),
+            spectators: self.spectators.len(),
+            playing: self.game_runtime.is_some(),
+        }
+    }
+
+    fn is_spectator(&self, connection_id: ConnectionId) -> bool {
+        self.spectators
+            .iter()
+            .any(|spectator| spectator.connection_id == connection_id)
+    }
+
+    fn seat_of(&self, connection_id: ConnectionId) -> Option<usize> {
+        self.seats
+            .iter()
+            .position(|seat| seat.connection_id() == Some(connection_id))
+    }
+
+    /// Whether anybody is left who isn't a bot, counting players who may still come back.
+    fn has_humans(&self) -> bool {
+        self.seats
+            .iter()
+            .any(|seat| matches!(seat, Seat::Human { .. } | Seat::Away { .. }))
+    }
+
+    fn join(
+        &mut self,
+        connection_id: ConnectionId,
+        name: &str,
+        token: String,
+        seat: Option<usize>,
+    ) -> Result<usize, &'static str> {
+        let seat = match seat {
+            Some(seat) if matches!(self.seats.get(seat), Some(Seat::Empty)) => seat,
+            Some(_) => return Err("seat is not available"),
+            None => self
+                .seats
+                .iter()
+                .position(|seat| matches!(seat, Seat::Empty))
+                .ok_or("room is full")?,
+        };
+
+        self.seats[seat] = Seat::Human {
+            connection_id,
+            name: name.to_owned(),
+            ready: false,
+            token,
+        };
+
+        Ok(seat)
+    }
+
+    /// Frees the connection's seat, or hands it to a bot if a game is on so the game can go on
+    /// without them.
+    fn leave(&mut self, connection_id: ConnectionId, bot_name: &'static str, rng: &mut StdRng) {
+        if let Some(seat) = self.seat_of(connection_id) {
+            if self.game_runtime.is_some() {
+                self.replace_with_bot(seat, bot_name, rng);
+            } else {
+                self.seats[seat] = Seat::Empty;
+            }
+        }
+        self.spectators
+            .retain(|spectator| spectator.connection_id != connection_id);
+    }
+
+    /// Keeps a dropped player's seat for them if a game is on, or frees it otherwise.
+    fn disconnect(&mut self, connection_id: ConnectionId) {
+        let Some(seat) = self.seat_of(connection_id) else {
+            self.spectators
+                .retain(|spectator| spectator.connection_id != connection_id);
+            return;
+        };
+
+        self.seats[seat] = match (&self.game_runtime, &self.seats[seat]) {
+            (Some(game_runtime), Seat::Human { name, token, .. }) => Seat::Away {
+                name: name.clone(),
+                token: token.clone(),
+                since: self.clock.now(),
+                events_seen: game_runtime.events().len(),
+            },
+            _ => Seat::Empty,
+        };
+    }
+
+    /// Gives the seat holding `token` to `connection_id`, returning the seat and the events the
+    /// player missed while they were away.
+    fn resume(&mut self, connection_id: ConnectionId, token: &str) -> Option<(usize, Vec<Event>)> {
+        let seat = self
+            .seats
+            .iter()
+            .position(|seat| seat.token() == Some(token))?;
+
+        let (name, events_seen) = match &self.seats[seat] {
+            // The old connection is still around, so there's no telling what it received.
+            Seat::Human { name, .. } => (name.clone(), 0),
+            Seat::Away {
+                name, events_seen, ..
+            } => (name.clone(), *events_seen),
+            _ => unreachable!("only humans hold session tokens"),
+        };
+
+        self.seats[seat] = Seat::Human {
+            connection_id,
+            name,
+            ready: self.game_runtime.is_some(),
+            token: token.to_owned(),
+        };
+
+        let missed_events = match &self.game_runtime {
+            Some(game_runtime) => game_runtime.events()[events_seen..].to_vec(),
+            None => vec![],
+        };
+
+        Some((seat, missed_events))
+    }
+
+    /// Hands the seat to a bot for the rest of the game.
+    fn replace_with_bot(&mut self, seat: usize, bot_name: &'static str, rng: &mut StdRng) {
+        let name = match self.seats[seat].name() {
+            Some(name) => format!("{} ({} bot)", name, bot_name),
+            None => format!("{} bot", bot_name),
+        };
+
+        self.seats[seat] = Seat::Bot {
+            name,
+            bot: bot_name,
+        };
+
+        if self.game_runtime.is_some() {
+            self.agents[seat] = bots::bot_by_name(bot_name, rng.gen()).unwrap();
+            self.actions[seat] = None;
+        }
+    }
+
+    /// Hands the seats of players who have been away for too long to bots, returning whether
+    /// there were any.
+    fn expire_sessions(&mut self, config: &ServerConfig, now: Instant, rng: &mut StdRng) -> bool {
+        let expired: Vec<usize> = (0..self.seats.len())
+            .filter(|&seat| match self.seats[seat] {
+                Seat::Away { since, .. } => now.duration_since(since) >= config.reconnect_grace,
+                _ => false,
+            })
+            .collect();
+
+        for &seat in &expired {
+            self.replace_with_bot(seat, config.replacement_bot, rng);
+        }
+
+        !expired.is_empty()
+    }
+
+    fn set_ready(&mut self, connection_id: ConnectionId, is_ready: bool) {
+        if let Some(seat) = self.seat_of(connection_id) {
+            if let Seat::Human { ready, .. } = &mut self.seats[seat] {
+                *ready = is_ready;
+            }
+        }
+    }
+
+    fn fill_with_bots(&mut self, bot_name: &'static str) {
+        for seat in &mut self.seats {
+            if let Seat::Empty = seat {
+                *seat = Seat::Bot {
+                    name: format!("{} bot", bot_name),
+                    bot: bot_name,
+                };
+            }
+        }
+    }
+
+    fn can_start(&self) -> bool {
+        self.game_runtime.is_none()
+            && self.seats.iter().all(|seat| match seat {
+                Seat::Empty | Seat::Away { .. } => false,
+                Seat::Human { ready, .. } => *ready,
+                Seat::Bot { .. } => true,
+            })
+    }
+
+    /// Sends the message to everybody in the room, spectators included.
+    fn broadcast(&self, outboxes: &Outboxes, message: ServerMessage) {
+        for connection_id in self.seats.iter().filter_map(Seat::connection_id) {
+            outboxes.send(connection_id, message.clone());
+        }
+        for spectator in &self.spectators {
+            outboxes.send(spectator.connection_id, message.clone());
+        }
+    }
+
+    /// Sends news about the game to everybody in the room, except that omniscient spectators get
+    /// it later, along with the rest of the delayed game.
+    fn broadcast_play(&mut self, outboxes: &Outboxes, message: ServerMessage) {
+        for connection_id in self.seats.iter().filter_map(Seat::connection_id) {
+            outboxes.send(connection_id, message.clone());
+        }
+        for spectator in self
+            .spectators
+            .iter()
+            .filter(|spectator| !spectator.omniscient)
+        {
+            outboxes.send(spectator.connection_id, message.clone());
+        }
+        self.delayed.push_back((self.clock.now(), message));
+    }
+
+    fn send_table(&self, outboxes: &Outboxes, spectator: &Spectator) {
+        if let (Some(game_runtime), false) = (&self.game_runtime, spectator.omniscient) {
+            outboxes.send(
+                spectator.connection_id,
+                ServerMessage::Table {
+                    view: SpectatorTableView::new(&SpectatorView::new(game_runtime)),
+                },
+            );
+        }
+    }
+
+    fn send_state(&self, outboxes: &Outboxes, seat: usize) {
+        let (Some(game_runtime), Some(connection_id)) =
+            (&self.game_runtime, self.seats[seat].connection_id())
+        else {
+            return;
+        };
+
+        let legal_actions = if seat == game_runtime.current_player_idx() {
+            game_runtime.legal_actions()
+        } else {
+            vec![]
+        };
+
+        outboxes.send(
+            connection_id,
+            ServerMessage::State {
+                view: TableView::new(&PlayerView::new(game_runtime, seat)),
+                legal_actions,
+            },
+        );
+    }
+
+    fn broadcast_state(&mut self, outboxes: &Outboxes) {
+        for seat in 0..self.seats.len() {
+            self.send_state(outboxes, seat);
+        }
+        for spectator in &self.spectators {
+            self.send_table(outboxes, spectator);
+        }
+
+        if let Some(game_runtime) = &self.game_runtime {
+            let view = SpectatorTableView::omniscient(&OmniscientView::new(game_runtime));
+            self.delayed
+                .push_back((self.clock.now(), ServerMessage::Table { view }));
+        }
+    }
+
+    /// Sends omniscient spectators whatever happened at least `delay` ago.
+    fn release_delayed(&mut self, outboxes: &Outboxes, now: Instant, delay: Duration) {
+        while let Some((happened_at, _)) = self.delayed.front() {
+            if now.duration_since(*happened_at) < delay {
+                break;
+            }
+
+            let (_, message) = self.delayed.pop_front().unwrap();
+            for spectator in self
+                .spectators
+                .iter()
+                .filter(|spectator| spectator.omniscient)
+            {
+                outboxes.send(spectator.connection_id, message.clone());
+            }
+        }
+    }
+
+    fn start(&mut self, outboxes: &Outboxes, seed: u64) -> Option<GameResult> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (agents, actions) = self
+            .seats
+            .iter()
+            .map(|seat| match seat {
+                Seat::Bot { bot, .. } => (bots::bot_by_name(bot, rng.gen()).unwrap(), None),
+                _ => {
+                    let (sender, receiver) = mpsc::channel();
+                    let agent: Box<dyn Agent> = Box::new(NetworkAgent { actions: receiver });
+                    (agent, Some(sender))
+                }
+            })
+            .unzip();
+
+        self.agents = agents;
+        self.actions = actions;
+        self.game_runtime = Some(GameRuntime::new(
+            self.seats.len(),
+            self.ruleset,
+            StdRng::seed_from_u64(rng.gen()),
+        ));
+        self.turn_started_at = self.clock.now();
+        self.timeouts = vec![0; self.seats.len()];
+
+        self.broadcast_state(outboxes);
+        self.play_turns(outboxes).unwrap()
+    }
+
+    fn act(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        action: Action,
+    ) -> Result<Option<GameResult>, String> {
+        if self.is_spectator(connection_id) {
+            return Err("spectators can't play".to_owned());
+        }
+
+        let seat = self.seat_of(connection_id).ok_or("not in a room")?;
+        let game_runtime = self
+            .game_runtime
+            .as_ref()
+            .ok_or("the game hasn't started yet")?;
+
+        if seat != game_runtime.current_player_idx() {
+            return Err("not your turn".to_owned());
+        }
+
+        self.timeouts[seat] = 0;
+        self.send_action(seat, action);
+
+        self.play_turns(outboxes)
+    }
+
+    /// Hands `action` to the agent of a seat players sat down at themselves.
+    fn send_action(&self, seat: usize, action: Action) {
+        if let Some(actions) = &self.actions[seat] {
+            actions.send(action).unwrap();
+        }
+    }
+
+    /// Plays every action that is ready to be played, be it a bot's or one a player sent, until
+    /// somebody has yet to decide or the game is over.
+    fn play_turns(&mut self, outboxes: &Outboxes) -> Result<Option<GameResult>, String> {
+        while self.game_runtime.is_some() {
+            match self.play_action(outboxes)? {
+                Step::Waiting => break,
+                Step::Played => {}
+                Step::GameOver(result) => return Ok(Some(result)),
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn turn_timed_out(&self, turn_timer: &TurnTimer, now: Instant) -> bool {
+        match &self.game_runtime {
+            Some(game_runtime) => {
+                !matches!(
+                    self.seats[game_runtime.current_player_idx()],
+                    Seat::Bot { .. }
+                ) && now.duration_since(self.turn_started_at) >= turn_timer.turn_time
+            }
+            None => false,
+        }
+    }
+
+    /// Plays out the current player's turn for them, handing their seat to a bot if they keep
+    /// timing out.
+    fn time_out(
+        &mut self,
+        outboxes: &Outboxes,
+        turn_timer: &TurnTimer,
+        replacement_bot: &'static str,
+        rng: &mut StdRng,
+    ) -> Option<GameResult> {
+        let seat = self.game_runtime.as_ref().unwrap().current_player_idx();
+
+        self.timeouts[seat] += 1;
+        self.broadcast_play(outboxes, ServerMessage::TimedOut { seat });
+
+        while let Some(game_runtime) = &self.game_runtime {
+            if game_runtime.current_player_idx() != seat {
+                break;
+            }
+
+            let view = PlayerView::new(game_runtime, seat);
+            let action = turn_timer
+                .on_timeout
+                .choose(&view, &game_runtime.legal_actions());
+            self.send_action(seat, action);
+            if let Step::GameOver(result) = self.play_action(outboxes).unwrap() {
+                return Some(result);
+            }
+        }
+
+        if let Some(replace_after) = turn_timer.replace_after {
+            if self.timeouts[seat] >= replace_after {
+                self.replace_with_bot(seat, replacement_bot, rng);
+            }
+        }
+
+        self.play_turns(outboxes).unwrap()
+    }
+
+    /// Plays the current player's action if their agent has one ready, tells everybody what
+    /// happened and wraps the game up if somebody won.
+    fn play_action(&mut self, outboxes: &Outboxes) -> Result<Step, String> {
+        let game_runtime = self.game_runtime.as_mut().unwrap();
+        let player_idx = game_runtime.current_player_idx();
+
+        let seen_events = match agent::play_next_action(game_runtime, &mut self.agents) {
+            Ok(Some(seen_events)) => seen_events,
+            Ok(None) => return Ok(Step::Waiting),
+            Err(GameError::IllegalAction { error, .. }) => return Err(error.to_string()),
+            Err(err) => return Err(err.to_string()),
+        };
+
+        if game_runtime.current_player_idx() != player_idx {
+            self.turn_started_at = self.clock.now();
+        }
+
+        let new_events = game_runtime.events()[seen_events..].to_vec();
+
+        for event in new_events {
+            self.broadcast_play(outboxes, ServerMessage::Event { event });
+        }
+
+        self.broadcast_state(outboxes);
+
+        let game_runtime = self.game_runtime.as_ref().unwrap();
+        let Some(winner) = game_runtime.winner() else {
+            return Ok(Step::Played);
+        };
+
+        let view = OmniscientView::new(game_runtime);
+        let points = view
+            .hands
+            .iter()
+            .flat_map(|hand| hand.iter())
+            .map(|&card| bots::card_score(card))
+            .sum();
+
+        let hands = view.hands.iter().map(|hand| hand.to_vec()).collect();
+        self.broadcast_play(
+            outboxes,
+            ServerMessage::GameOver {
+                winner,
+                hands,
+                points,
+            },
+        );
+
+        let result = GameResult {
+            names: self
+                .seats
+                .iter()
+                .map(|seat| seat.name().unwrap_or_default().to_owned())
+                .collect(),
+            winner,
+            points,
+        };
+
+        // The room goes back to waiting, so the same players can ready up for another game.
+        self.game_runtime = None;
+        self.agents.clear();
+        self.actions.clear();
+        for seat in &mut self.seats {
+            match seat {
+                Seat::Human { ready, .. } => *ready = false,
+                Seat::Away { .. } => *seat = Seat::Empty,
+                _ => {}
+            }
+        }
+
+        Ok(Step::GameOver(result))
+    }
+}
+
+/// Every room on the server, who is in which, and the scores of every finished game.
+pub(crate) struct Lobby {
+    rooms: BTreeMap<RoomId, Room>,
+    next_room_id: RoomId,
+    names: HashMap<ConnectionId, String>,
+    locations: HashMap<ConnectionId, RoomId>,
+    scores: BTreeMap<String, PlayerScore>,
+    rng: StdRng,
+    config: ServerConfig,
+    clock: Arc<dyn Clock>,
+}
+
+impl Lobby {
+    pub(crate) fn new(config: ServerConfig, clock: Arc<dyn Clock>) -> Lobby {
+        Lobby {
+            rooms: BTreeMap::new(),
+            next_room_id: 1,
+            names: HashMap::new(),
+            locations: HashMap::new(),
+            scores: BTreeMap::new(),
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            clock,
+        }
+    }
+
+    /// Plays for everybody who has run out of time, hands the seats of players who didn't come
+    /// back in time to bots and catches omniscient spectators up. Called regularly by the server.
+    pub(crate) fn tick(&mut self, outboxes: &Outboxes) {
+        let now = self.clock.now();
+        let room_ids: Vec<RoomId> = self.rooms.keys().copied().collect();
+
+        for room_id in room_ids {
+            let room = self.rooms.get_mut(&room_id).unwrap();
+            let mut seats_changed = room.expire_sessions(&self.config, now, &mut self.rng);
+            let mut result = if seats_changed {
+                room.play_turns(outboxes).unwrap()
+            } else {
+                None
+            };
+
+            if let Some(turn_timer) = &self.config.turn_timer {
+                if result.is_none() && room.turn_timed_out(turn_timer, now) {
+                    result = room.time_out(
+                        outboxes,
+                        turn_timer,
+                        self.config.replacement_bot,
+                        &mut self.rng,
+                    );
+                    seats_changed = true;
+                }
+            }
+
+            self.evict_unseated(outboxes, room_id);
+
+            if let Some(result) = result {
+                self.finish_game(outboxes, room_id, result);
+            } else if seats_changed {
+                let room = &self.rooms[&room_id];
+                room.broadcast(
+                    outboxes,
+                    ServerMessage::Room {
+                        room: room.summary(room_id),
+                    },
+                );
+            }
+
+            let room = self.rooms.get_mut(&room_id).unwrap();
+            room.release_delayed(outboxes, now, self.config.spectator_delay);
+
+            if !room.has_humans() {
+                self.close_room(outboxes, room_id);
+            }
+        }
+    }
+
+    /// Removes a room nobody is playing in any more, sending its spectators away.
+    fn close_room(&mut self, outboxes: &Outboxes, room_id: RoomId) {
+        let room = self.rooms.remove(&room_id).unwrap();
+
+        for spectator in room.spectators {
+            self.locations.remove(&spectator.connection_id);
+            outboxes.send(spectator.connection_id, ServerMessage::Left { room_id });
+        }
+    }
+
+    /// Tells connections that lost their seat in `room_id`, to a bot or to a newer connection of
+    /// the same player, that they are out.
+    fn evict_unseated(&mut self, outboxes: &Outboxes, room_id: RoomId) {
+        let room = &self.rooms[&room_id];
+        let evicted: Vec<ConnectionId> = self
+            .locations
+            .iter()
+            .filter(|&(&connection_id, &location)| {
+                location == room_id
+                    && room.seat_of(connection_id).is_none()
+                    && !room.is_spectator(connection_id)
+            })
+            .map(|(&connection_id, _)| connection_id)
+            .collect();
+
+        for connection_id in evicted {
+            self.locations.remove(&connection_id);
+            outboxes.send(connection_id, ServerMessage::Left { room_id });
+        }
+    }
+
+    pub(crate) fn handle(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        message: ClientMessage,
+    ) {
+        let result = match message {
+            ClientMessage::SetName { name } => self.set_name(connection_id, name),
+            ClientMessage::ListRooms => {
+                outboxes.send(
+                    connection_id,
+                    ServerMessage::Rooms {
+                        rooms: self
+                            .rooms
+                            .iter()
+                            .map(|(&room_id, room)| room.summary(room_id))
+                            .collect(),
+                    },
+                );
+                Ok(())
+            }
+            ClientMessage::CreateRoom { ruleset, seats } => {
+                self.create_room(outboxes, connection_id, ruleset, seats)
+            }
+            ClientMessage::JoinRoom { room_id, seat } => {
+                self.join_room(outboxes, connection_id, room_id, seat)
+            }
+            ClientMessage::Spectate {
+                room_id,
+                omniscient,
+            } => self.spectate(outboxes, connection_id, room_id, omniscient),
+            ClientMessage::LeaveRoom => self.leave_room(outboxes, connection_id),
+            ClientMessage::Resume { token } => self.resume(outboxes, connection_id, &token),
+            ClientMessage::Ready { ready } => self.update_room(outboxes, connection_id, |room| {
+                room.set_ready(connection_id, ready);
+                Ok(())
+            }),
+            ClientMessage::FillWithBots { bot } => {
+                match LOBBY_BOTS.into_iter().find(|&name| name == bot) {
+                    Some(bot) => self.update_room(outboxes, connection_id, |room| {
+                        room.fill_with_bots(bot);
+                        Ok(())
+                    }),
+                    None => Err(format!("unknown bot {:?}", bot)),
+                }
+            }
+            ClientMessage::Action { action } => self.act(outboxes, connection_id, action),
+            ClientMessage::Scores => {
+                self.send_scores(outboxes, connection_id);
+                Ok(())
+            }
+        };
+
+        if let Err(message) = result {
+            outboxes.send_error(connection_id, &message);
+        }
+    }
+
+    /// Keeps the connection's seat for it if a game is on, and otherwise frees it, closing the
+    /// room if nobody else is left in it.
+    pub(crate) fn disconnect(&mut self, outboxes: &Outboxes, connection_id: ConnectionId) {
+        self.names.remove(&connection_id);
+
+        let Some(room_id) = self.locations.remove(&connection_id) else {
+            return;
+        };
+        let room = self.rooms.get_mut(&room_id).unwrap();
+
+        room.disconnect(connection_id);
+
+        if room.has_humans() {
+            room.broadcast(
+                outboxes,
+                ServerMessage::Room {
+                    room: room.summary(room_id),
+                },
+            );
+        } else {
+            self.close_room(outboxes, room_id);
+        }
+    }
+
+    fn resume(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        token: &str,
+    ) -> Result<(), String> {
+        if self.locations.contains_key(&connection_id) {
+            return Err("already in a room".to_owned());
+        }
+
+        let (room_id, seat, missed_events) = self
+            .rooms
+            .iter_mut()
+            .find_map(|(&room_id, room)| {
+                room.resume(connection_id, token)
+                    .map(|(seat, missed_events)| (room_id, seat, missed_events))
+            })
+            .ok_or("unknown or expired session")?;
+
+        self.locations.insert(connection_id, room_id);
+        self.evict_unseated(outboxes, room_id);
+
+        let room = &self.rooms[&room_id];
+        outboxes.send(
+            connection_id,
+            ServerMessage::Resumed {
+                room_id,
+                seat,
+                missed_events,
+            },
+        );
+        room.broadcast(
+            outboxes,
+            ServerMessage::Room {
+                room: room.summary(room_id),
+            },
+        );
+        room.send_state(outboxes, seat);
+
+        Ok(())
+    }
+
+    fn name(&self, connection_id: ConnectionId) -> String {
+        self.names
+            .get(&connection_id)
+            .cloned()
+            .unwrap_or_else(|| format!("player {}", connection_id + 1))
+    }
+
+    fn set_name(&mut self, connection_id: ConnectionId, name: String) -> Result<(), String> {
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err("name can't be empty".to_owned());
+        }
+
+        if self.locations.contains_key(&connection_id) {
+            return Err("can't change name while in a room".to_owned());
+        }
+
+        self.names.insert(connection_id, name.to_owned());
+        Ok(())
+    }
+
+    fn create_room(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        ruleset_name: String,
+        num_seats: usize,
+    ) -> Result<(), String> {
+        if self.locations.contains_key(&connection_id) {
+            return Err("already in a room".to_owned());
+        }
+
+        let ruleset = Ruleset::by_name(&ruleset_name)
+            .ok_or_else(|| format!("unknown ruleset {:?}", ruleset_name))?;
+
+        let max_seats = MAX_SEATS.min(ruleset.max_players());
+        if !(2..=max_seats).contains(&num_seats) {
+            return Err(format!("a room needs 2 to {} seats", max_seats));
+        }
+
+        let room_id = self.next_room_id;
+        self.next_room_id += 1;
+
+        self.rooms.insert(
+            room_id,
+            Room {
+                ruleset_name,
+                ruleset,
+                seats: (0..num_seats).map(|_| Seat::Empty).collect(),
+                game_runtime: None,
+                agents: vec![],
+                actions: vec![],
+                clock: self.clock.clone(),
+                turn_started_at: self.clock.now(),
+                timeouts: vec![0; num_seats],
+                spectators: vec![],
+                delayed: VecDeque::new(),
+            },
+        );
+
+        self.join_room(outboxes, connection_id, room_id, Some(0))
+    }
+
+    fn join_room(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        room_id: RoomId,
+        seat: Option<usize>,
+    ) -> Result<(), String> {
+        if self.locations.contains_key(&connection_id) {
+            return Err("already in a room".to_owned());
+        }
+
+        let name = self.name(connection_id);
+        let token = format!("{:032x}", rand::random::<u128>());
+        let room = self.rooms.get_mut(&room_id).ok_or("no such room")?;
+        let seat = room.join(connection_id, &name, token.clone(), seat)?;
+        self.locations.insert(connection_id, room_id);
+
+        outboxes.send(
+            connection_id,
+            ServerMessage::Joined {
+                room_id,
+                seat,
+                token,
+            },
+        );
+        room.broadcast(
+            outboxes,
+            ServerMessage::Room {
+                room: room.summary(room_id),
+            },
+        );
+        room.send_state(outboxes, seat);
+
+        Ok(())
+    }
+
+    fn spectate(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        room_id: RoomId,
+        omniscient: bool,
+    ) -> Result<(), String> {
+        if self.locations.contains_key(&connection_id) {
+            return Err("already in a room".to_owned());
+        }
+
+        let room = self.rooms.get_mut(&room_id).ok_or("no such room")?;
+        let spectator = Spectator {
+            connection_id,
+            omniscient,
+        };
+
+        outboxes.send(
+            connection_id,
+            ServerMessage::Spectating {
+                room_id,
+                omniscient,
+                delay_seconds: if omniscient {
+                    self.config.spectator_delay.as_secs()
+                } else {
+                    0
+                },
+            },
+        );
+        room.send_table(outboxes, &spectator);
+        room.spectators.push(spectator);
+        self.locations.insert(connection_id, room_id);
+
+        room.broadcast(
+            outboxes,
+            ServerMessage::Room {
+                room: room.summary(room_id),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn leave_room(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+    ) -> Result<(), String> {
+        let room_id = self
+            .locations
+            .remove(&connection_id)
+            .ok_or("not in a room")?;
+        let room = self.rooms.get_mut(&room_id).unwrap();
+
+        room.leave(connection_id, self.config.replacement_bot, &mut self.rng);
+        outboxes.send(connection_id, ServerMessage::Left { room_id });
+
+        if !room.has_humans() {
+            self.close_room(outboxes, room_id);
+            return Ok(());
+        }
+
+        // It may have been the leaving player's turn.
+        match room.play_turns(outboxes).unwrap() {
+            Some(result) => self.finish_game(outboxes, room_id, result),
+            None => room.broadcast(
+                outboxes,
+                ServerMessage::Room {
+                    room: room.summary(room_id),
+                },
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Changes the connection's room, then starts its game if that made everybody ready.
+    fn update_room(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        update: impl FnOnce(&mut Room) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let &room_id = self.locations.get(&connection_id).ok_or("not in a room")?;
+        let room = self.rooms.get_mut(&room_id).unwrap();
+
+        if room.is_spectator(connection_id) {
+            return Err("spectators can't change the room".to_owned());
+        }
+
+        update(room)?;
+        room.broadcast(
+            outboxes,
+            ServerMessage::Room {
+                room: room.summary(room_id),
+            },
+        );
+
+        if room.can_start() {
+            if let Some(result) = room.start(outboxes, self.rng.gen()) {
+                self.finish_game(outboxes, room_id, result);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn act(
+        &mut self,
+        outboxes: &Outboxes,
+        connection_id: ConnectionId,
+        action: Action,
+    ) -> Result<(), String> {
+        let &room_id = self.locations.get(&connection_id).ok_or("not in a room")?;
+        let room = self.rooms.get_mut(&room_id).unwrap();
+
+        if let Some(result) = room.act(outboxes, connection_id, action)? {
+            self.finish_game(outboxes, room_id, result);
+        }
+
+        Ok(())
+    }
+
+    fn finish_game(&mut self, outboxes: &Outboxes, room_id: RoomId, result: GameResult) {
+        for (seat, name) in result.names.iter().enumerate() {
+            let score = self
+                .scores
+                .entry(name.clone())
+                .or_insert_with(|| PlayerScore {
+                    name: name.clone(),
+                    ..PlayerScore::default()
+                });
+
+            score.games += 1;
+            if seat == result.winner {
+                score.wins += 1;
+                score.points += result.points;
+            }
+        }
+
+        let room = &self.rooms[&room_id];
+        room.broadcast(
+            outboxes,
+            ServerMessage::Room {
+                room: room.summary(room_id),
+            },
+        );
+
+        for connection_id in room.seats.iter().filter_map(Seat::connection_id) {
+            self.send_scores(outboxes, connection_id);
+        }
+    }
+
+    fn send_scores(&self, outboxes: &Outboxes, connection_id: ConnectionId) {
+        outboxes.send(
+            connection_id,
+            ServerMessage::Scores {
+                scores: self.scores.values().cloned().collect(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::game_executor::Event;
+    use crate::game_state::{Action, DrawAmount};
+    use crate::timer::{ManualClock, SystemClock, TimeoutAction};
+
+    struct TestClient {
+        connection_id: ConnectionId,
+        inbox: Receiver<ServerMessage>,
+    }
+
+    impl TestClient {
+        fn connect(outboxes: &mut Outboxes, connection_id: ConnectionId) -> TestClient {
+            let (outbox, inbox) = mpsc::channel();
+            outboxes.by_connection.insert(connection_id, outbox);
+            TestClient {
+                connection_id,
+                inbox,
+            }
+        }
+
+        fn messages(&self) -> Vec<ServerMessage> {
+            self.inbox.try_iter().collect()
+        }
+    }
+
+    fn rooms(lobby: &mut Lobby, outboxes: &Outboxes, client: &TestClient) -> Vec<RoomSummary> {
+        lobby.handle(outboxes, client.connection_id, ClientMessage::ListRooms);
+        match client.messages().pop() {
+            Some(ServerMessage::Rooms { rooms }) => rooms,
+            message => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn players_create_list_join_and_leave_rooms() {
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let bob = TestClient::connect(&mut outboxes, 1);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(SystemClock));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::SetName {
+                name: "alice".to_owned(),
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "quick".to_owned(),
+                seats: 3,
+            },
+        );
+        assert!(matches!(
+            alice.messages()[0],
+            ServerMessage::Joined {
+                room_id: 1,
+                seat: 0,
+                ..
+            }
+        ));
+
+        let listed = rooms(&mut lobby, &outboxes, &bob);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].ruleset, "quick");
+        assert_eq!(listed[0].seats[0].as_ref().unwrap().name, "alice");
+        assert_eq!(listed[0].seats[1], None);
+
+        lobby.handle(
+            &outboxes,
+            bob.connection_id,
+            ClientMessage::JoinRoom {
+                room_id: 1,
+                seat: Some(0),
+            },
+        );
+        assert_eq!(
+            bob.messages(),
+            vec![ServerMessage::Error {
+                message: "seat is not available".to_owned()
+            }]
+        );
+
+        lobby.handle(
+            &outboxes,
+            bob.connection_id,
+            ClientMessage::JoinRoom {
+                room_id: 1,
+                seat: None,
+            },
+        );
+        assert!(matches!(
+            bob.messages()[0],
+            ServerMessage::Joined {
+                room_id: 1,
+                seat: 1,
+                ..
+            }
+        ));
+
+        lobby.handle(&outboxes, alice.connection_id, ClientMessage::LeaveRoom);
+        lobby.disconnect(&outboxes, bob.connection_id);
+
+        assert!(rooms(&mut lobby, &outboxes, &alice).is_empty());
+    }
+
+    #[test]
+    fn game_starts_once_everybody_is_ready() {
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let bob = TestClient::connect(&mut outboxes, 1);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(SystemClock));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 2,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            bob.connection_id,
+            ClientMessage::JoinRoom {
+                room_id: 1,
+                seat: None,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Ready { ready: true },
+        );
+        alice.messages();
+
+        assert!(!rooms(&mut lobby, &outboxes, &alice)[0].playing);
+
+        lobby.handle(
+            &outboxes,
+            bob.connection_id,
+            ClientMessage::Ready { ready: true },
+        );
+
+        assert!(bob
+            .messages()
+            .iter()
+            .any(|message| matches!(message, ServerMessage::State { .. })));
+        assert!(rooms(&mut lobby, &outboxes, &alice)[0].playing);
+    }
+
+    #[test]
+    fn rooms_only_seat_bots_that_answer_at_once() {
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(SystemClock));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 2,
+            },
+        );
+        alice.messages();
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::FillWithBots {
+                bot: "mcts".to_owned(),
+            },
+        );
+        assert_eq!(
+            alice.messages(),
+            vec![ServerMessage::Error {
+                message: "unknown bot \"mcts\"".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn bots_fill_empty_seats_and_scores_return_to_the_lobby() {
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(SystemClock));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 3,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::FillWithBots {
+                bot: "greedy".to_owned(),
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Ready { ready: true },
+        );
+
+        let scores = loop {
+            let messages = alice.messages();
+            if let Some(ServerMessage::Scores { scores }) = messages.last() {
+                break scores.clone();
+            }
+
+            let legal_actions = messages
+                .iter()
+                .rev()
+                .find_map(|message| match message {
+                    ServerMessage::State { legal_actions, .. } => Some(legal_actions.clone()),
+                    _ => None,
+                })
+                .unwrap();
+
+            lobby.handle(
+                &outboxes,
+                alice.connection_id,
+                ClientMessage::Action {
+                    action: *legal_actions.last().unwrap(),
+                },
+            );
+        };
+
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0].name, "greedy bot");
+        assert_eq!(scores[0].games, 2);
+        assert_eq!(scores[1].name, "player 1");
+        assert_eq!(scores[1].games, 1);
+        assert_eq!(scores.iter().map(|score| score.wins).sum::<usize>(), 1);
+        assert!(!rooms(&mut lobby, &outboxes, &alice)[0].playing);
+    }
+
+    #[test]
+    fn leaving_mid_game_hands_the_seat_to_a_bot() {
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let bob = TestClient::connect(&mut outboxes, 1);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(SystemClock));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 2,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            bob.connection_id,
+            ClientMessage::JoinRoom {
+                room_id: 1,
+                seat: None,
+            },
+        );
+        for client in [&alice, &bob] {
+            lobby.handle(
+                &outboxes,
+                client.connection_id,
+                ClientMessage::Ready { ready: true },
+            );
+        }
+        bob.messages();
+
+        // It's the leaving player's turn, so the bot has to take it right away.
+        lobby.handle(&outboxes, alice.connection_id, ClientMessage::LeaveRoom);
+        assert!(alice
+            .messages()
+            .contains(&ServerMessage::Left { room_id: 1 }));
+
+        let scores = loop {
+            let messages = bob.messages();
+            if let Some(ServerMessage::Scores { scores }) = messages.last() {
+                break scores.clone();
+            }
+
+            let legal_actions = messages
+                .iter()
+                .rev()
+                .find_map(|message| match message {
+                    ServerMessage::State { legal_actions, .. } => Some(legal_actions.clone()),
+                    _ => None,
+                })
+                .unwrap();
+            assert!(!legal_actions.is_empty(), "the game stalled");
+
+            lobby.handle(
+                &outboxes,
+                bob.connection_id,
+                ClientMessage::Action {
+                    action: *legal_actions.last().unwrap(),
+                },
+            );
+        };
+
+        let names: Vec<&str> = scores.iter().map(|score| score.name.as_str()).collect();
+        assert_eq!(names, vec!["player 1 (heuristic bot)", "player 2"]);
+    }
+
+    #[test]
+    fn idle_players_time_out_and_get_replaced_by_bots() {
+        let clock = ManualClock::new();
+        let turn_timer = TurnTimer {
+            turn_time: Duration::from_secs(30),
+            on_timeout: TimeoutAction::DrawAndPass,
+            replace_after: Some(2),
+        };
+        let config = ServerConfig {
+            turn_timer: Some(turn_timer),
+            replacement_bot: "greedy",
+            ..ServerConfig::default()
+        };
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let mut lobby = Lobby::new(config, Arc::new(clock.clone()));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 2,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::FillWithBots {
+                bot: "random".to_owned(),
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Ready { ready: true },
+        );
+        alice.messages();
+
+        clock.advance(Duration::from_secs(29));
+        lobby.tick(&outboxes);
+        assert!(alice.messages().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        lobby.tick(&outboxes);
+        let messages = alice.messages();
+        assert_eq!(messages[0], ServerMessage::TimedOut { seat: 0 });
+        assert!(messages.contains(&ServerMessage::Event {
+            event: Event::Passed { player_idx: 0 }
+        }));
+        assert_eq!(rooms(&mut lobby, &outboxes, &alice).len(), 1);
+
+        // The timer starts over on every turn.
+        clock.advance(Duration::from_secs(29));
+        lobby.tick(&outboxes);
+        assert!(alice.messages().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        lobby.tick(&outboxes);
+        let messages = alice.messages();
+        assert_eq!(messages[0], ServerMessage::TimedOut { seat: 0 });
+        assert!(messages.contains(&ServerMessage::Left { room_id: 1 }));
+
+        // With no humans left, the bots finished the game and the room closed.
+        assert!(rooms(&mut lobby, &outboxes, &alice).is_empty());
+        lobby.handle(&outboxes, alice.connection_id, ClientMessage::Scores);
+        let Some(ServerMessage::Scores { scores }) = alice.messages().pop() else {
+            panic!("expected scores");
+        };
+        assert_eq!(scores[0].name, "player 1 (greedy bot)");
+    }
+
+    /// Seats alice against a random bot and starts the game, returning her session token.
+    fn start_game_against_bot(
+        lobby: &mut Lobby,
+        outboxes: &Outboxes,
+        alice: &TestClient,
+    ) -> String {
+        lobby.handle(
+            outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 2,
+            },
+        );
+        lobby.handle(
+            outboxes,
+            alice.connection_id,
+            ClientMessage::FillWithBots {
+                bot: "random".to_owned(),
+            },
+        );
+        lobby.handle(
+            outboxes,
+            alice.connection_id,
+            ClientMessage::Ready { ready: true },
+        );
+
+        match alice.messages().swap_remove(0) {
+            ServerMessage::Joined { token, .. } => token,
+            message => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn players_resume_their_seat_after_reconnecting() {
+        let clock = ManualClock::new();
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(clock.clone()));
+        let token = start_game_against_bot(&mut lobby, &outboxes, &alice);
+
+        lobby.disconnect(&outboxes, alice.connection_id);
+        let seats = &lobby.rooms[&1].seats;
+        assert!(seats[0].summary().unwrap().away);
+
+        let alice = TestClient::connect(&mut outboxes, 1);
+        clock.advance(Duration::from_secs(59));
+        lobby.tick(&outboxes);
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Resume {
+                token: "nonsense".to_owned(),
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Resume {
+                token: token.clone(),
+            },
+        );
+
+        let messages = alice.messages();
+        assert_eq!(
+            messages[0],
+            ServerMessage::Error {
+                message: "unknown or expired session".to_owned()
+            }
+        );
+        assert_eq!(
+            messages[1],
+            ServerMessage::Resumed {
+                room_id: 1,
+                seat: 0,
+                missed_events: vec![],
+            }
+        );
+        let Some(ServerMessage::State {
+            view,
+            legal_actions,
+        }) = messages.last()
+        else {
+            panic!("expected a snapshot of the game");
+        };
+        assert_eq!(view.seat, 0);
+        assert_eq!(view.current_player_idx, 0);
+        assert!(!legal_actions.is_empty());
+        assert!(!lobby.rooms[&1].seats[0].summary().unwrap().away);
+
+        // Events played while away are sent on resuming, and only those.
+        lobby.disconnect(&outboxes, alice.connection_id);
+        let room = lobby.rooms.get_mut(&1).unwrap();
+        let events_before = room.game_runtime.as_ref().unwrap().events().len();
+        room.send_action(
+            0,
+            Action::DrawCard {
+                amount: DrawAmount::One,
+            },
+        );
+        assert!(matches!(room.play_turns(&outboxes), Ok(None)));
+        let played = room.game_runtime.as_ref().unwrap().events()[events_before..].to_vec();
+
+        let alice = TestClient::connect(&mut outboxes, 2);
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Resume { token },
+        );
+        assert_eq!(
+            alice.messages()[0],
+            ServerMessage::Resumed {
+                room_id: 1,
+                seat: 0,
+                missed_events: played,
+            }
+        );
+    }
+
+    #[test]
+    fn bots_take_over_seats_of_players_who_do_not_come_back() {
+        let clock = ManualClock::new();
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let bob = TestClient::connect(&mut outboxes, 1);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(clock.clone()));
+        let token = start_game_against_bot(&mut lobby, &outboxes, &alice);
+
+        lobby.disconnect(&outboxes, alice.connection_id);
+        clock.advance(Duration::from_secs(60));
+        lobby.tick(&outboxes);
+
+        // With alice gone for good, the bots finished the game and the room closed.
+        assert!(rooms(&mut lobby, &outboxes, &bob).is_empty());
+        lobby.handle(
+            &outboxes,
+            bob.connection_id,
+            ClientMessage::Resume { token },
+        );
+        assert_eq!(
+            bob.messages(),
+            vec![ServerMessage::Error {
+                message: "unknown or expired session".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn spectators_watch_without_playing() {
+        let clock = ManualClock::new();
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let watcher = TestClient::connect(&mut outboxes, 1);
+        let commentator = TestClient::connect(&mut outboxes, 2);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(clock.clone()));
+        start_game_against_bot(&mut lobby, &outboxes, &alice);
+
+        lobby.handle(
+            &outboxes,
+            watcher.connection_id,
+            ClientMessage::Spectate {
+                room_id: 1,
+                omniscient: false,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            commentator.connection_id,
+            ClientMessage::Spectate {
+                room_id: 1,
+                omniscient: true,
+            },
+        );
+
+        let messages = watcher.messages();
+        assert_eq!(
+            messages[0],
+            ServerMessage::Spectating {
+                room_id: 1,
+                omniscient: false,
+                delay_seconds: 0
+            }
+        );
+        let ServerMessage::Table { view } = &messages[1] else {
+            panic!("expected the table");
+        };
+        assert_eq!(view.hands, None);
+        assert_eq!(view.hand_sizes, vec![7, 7]);
+
+        for spectator in [&watcher, &commentator] {
+            lobby.handle(
+                &outboxes,
+                spectator.connection_id,
+                ClientMessage::Action {
+                    action: Action::DrawCard {
+                        amount: DrawAmount::One,
+                    },
+                },
+            );
+            lobby.handle(
+                &outboxes,
+                spectator.connection_id,
+                ClientMessage::Ready { ready: false },
+            );
+            let messages = spectator.messages();
+            assert!(messages.contains(&ServerMessage::Error {
+                message: "spectators can't play".to_owned()
+            }));
+            assert!(messages.contains(&ServerMessage::Error {
+                message: "spectators can't change the room".to_owned()
+            }));
+        }
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Action {
+                action: Action::DrawCard {
+                    amount: DrawAmount::One,
+                },
+            },
+        );
+        let event = ServerMessage::Event {
+            event: Event::Drew {
+                player_idx: 0,
+                amount: 1,
+            },
+        };
+        assert!(watcher.messages().contains(&event));
+        assert!(commentator.messages().is_empty());
+
+        // The omniscient view, with every hand, only shows up once the delay has passed.
+        clock.advance(Duration::from_secs(29));
+        lobby.tick(&outboxes);
+        assert!(commentator.messages().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        lobby.tick(&outboxes);
+        let messages = commentator.messages();
+        assert!(messages.contains(&event));
+        let Some(ServerMessage::Table { view }) = messages.last() else {
+            panic!("expected the table");
+        };
+        assert_eq!(view.hands.as_ref().unwrap()[0].len(), 8);
+
+        // Spectators are sent away once the players are gone.
+        lobby.handle(&outboxes, alice.connection_id, ClientMessage::LeaveRoom);
+        assert!(watcher
+            .messages()
+            .contains(&ServerMessage::Left { room_id: 1 }));
+        assert!(rooms(&mut lobby, &outboxes, &watcher).is_empty());
+    }
+
+    #[test]
+    fn lobby_rejects_invalid_rooms() {
+        let mut outboxes = Outboxes::default();
+        let alice = TestClient::connect(&mut outboxes, 0);
+        let mut lobby = Lobby::new(ServerConfig::default(), Arc::new(SystemClock));
+
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "nonsense".to_owned(),
+                seats: 2,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 1,
+            },
+        );
+        lobby.handle(
+            &outboxes,
+            alice.connection_id,
+            ClientMessage::Ready { ready: true },
+        );
+
+        assert_eq!(
+            alice.messages(),
+            vec![
+                ServerMessage::Error {
+                    message: "unknown ruleset \"nonsense\"".to_owned()
+                },
+                ServerMessage::Error {
+                    message: "a room needs 2 to 10 seats".to_owned()
+                },
+                ServerMessage::Error {
+                    message: "not in a room".to_owned()
+                },
+            ]
+        );
+    }
+}
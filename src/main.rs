@@ -1,6 +1,97 @@
-mod game_state;
-mod game_executor;
+use std::process;
+use std::time::Duration;
 
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use uno::cli::{
+    bot_by_name, human_agents, play_game, Agent, AgentError, GameError, MctsBot, SearchBudget,
+    BOT_NAMES,
+};
+use uno::{GameRuntime, Ruleset};
+
+const USAGE: &str = "usage: uno [--players N] [--humans N] [--bot NAME] [--think-ms MS] \
+                     [--ruleset NAME] [--seed SEED]";
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    eprintln!("bots: {}", BOT_NAMES.join(", "));
+    eprintln!("rulesets: {}", Ruleset::NAMES.join(", "));
+    process::exit(2);
+}
+
+/// Plays a game at this terminal against bots, or against other humans taking turns at the
+/// keyboard.
 fn main() {
-    println!("Hello, world!");
+    let mut num_players = 2usize;
+    let mut num_humans = 1usize;
+    let mut bot_name = "heuristic".to_owned();
+    let mut seed = None;
+    let mut think_time = None;
+    let mut ruleset = Ruleset::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_else(|| exit_with_usage());
+        match (arg.as_str(), value.parse::<u64>().ok()) {
+            ("--players", Some(value)) if value >= 2 => num_players = value as usize,
+            ("--humans", Some(value)) => num_humans = value as usize,
+            ("--seed", Some(value)) => seed = Some(value),
+            ("--think-ms", Some(value)) => think_time = Some(Duration::from_millis(value)),
+            ("--bot", _) => bot_name = value,
+            ("--ruleset", _) => {
+                ruleset = Ruleset::by_name(&value).unwrap_or_else(|| exit_with_usage());
+            }
+            _ => exit_with_usage(),
+        }
+    }
+
+    if num_humans > num_players || num_players > ruleset.max_players() {
+        exit_with_usage();
+    }
+    if let Err(err) = bot_by_name(&bot_name, 0) {
+        eprintln!("uno: {}", err);
+        process::exit(2);
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let seats: Vec<usize> = (0..num_humans).collect();
+    let mut agents: Vec<Box<dyn Agent>> = match human_agents(&seats) {
+        Ok(agents) => agents
+            .into_iter()
+            .map(|agent| Box::new(agent) as Box<dyn Agent>)
+            .collect(),
+        Err(err) => {
+            eprintln!("uno: {}", err);
+            process::exit(1);
+        }
+    };
+
+    for _ in num_humans..num_players {
+        agents.push(match (bot_name.as_str(), think_time) {
+            ("mcts", Some(think_time)) => {
+                Box::new(MctsBot::new(rng.gen(), SearchBudget::Time(think_time)))
+            }
+            _ => bot_by_name(&bot_name, rng.gen()).unwrap(),
+        });
+    }
+
+    let mut game_runtime = GameRuntime::from_seed(num_players, ruleset, rng.gen());
+
+    match play_game(&mut game_runtime, &mut agents) {
+        Ok(_)
+        | Err(GameError::Agent {
+            error: AgentError::Left,
+            ..
+        }) => {}
+        Err(err) => {
+            // Restores the terminal before printing the error.
+            drop(agents);
+            eprintln!("uno: {}", err);
+            process::exit(1);
+        }
+    }
 }
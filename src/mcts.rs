@@ -0,0 +1,488 @@
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::agent::{Agent, AgentError};
+use crate::bots;
+use crate::game_executor::{Event, GameRuntime};
+use crate::game_state::{Action, Card, CardColor, Deck, WildCardKind};
+use crate::view::PlayerView;
+
+/// Balances trying out rarely visited moves against exploiting the best known ones.
+const EXPLORATION: f64 = 0.7;
+
+/// Playouts that go on for this long are scored as a loss for everybody, so that a
+/// pathological draw-and-pass loop can't stall the search.
+const MAX_PLAYOUT_ACTIONS: usize = 2000;
+
+/// A few hundredths of a second per move in a release build.
+pub(crate) const DEFAULT_BUDGET: SearchBudget = SearchBudget::Iterations(2000);
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SearchBudget {
+    Iterations(usize),
+    // Set from the command line, which browsers don't have.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    Time(Duration),
+}
+
+struct Node {
+    /// The action that led here from the parent, and the player who took it.
+    action: Option<(usize, Action)>,
+    children: Vec<usize>,
+    visits: u32,
+    wins: f64,
+    /// How many times this node's action was legal when its parent was visited.
+    availability: u32,
+}
+
+impl Node {
+    fn ucb(&self) -> f64 {
+        self.wins / self.visits as f64
+            + EXPLORATION * ((self.availability as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+/// Information-set Monte Carlo tree search: every iteration guesses the cards this player can't
+/// see, then searches that guess while sharing a single tree across all of them.
+pub struct MctsBot {
+    rng: StdRng,
+    budget: SearchBudget,
+}
+
+impl MctsBot {
+    pub fn new(seed: u64, budget: SearchBudget) -> MctsBot {
+        MctsBot {
+            rng: StdRng::seed_from_u64(seed),
+            budget,
+        }
+    }
+
+    fn search(&mut self, view: &PlayerView, legal_actions: &[Action]) -> Action {
+        let mut nodes = vec![Node {
+            action: None,
+            children: vec![],
+            visits: 0,
+            wins: 0.0,
+            availability: 0,
+        }];
+
+        // Only timed searches read the clock, which browsers don't have.
+        let deadline = match self.budget {
+            SearchBudget::Time(max_duration) => Some(Instant::now() + max_duration),
+            SearchBudget::Iterations(_) => None,
+        };
+        let mut iterations = 0;
+
+        loop {
+            match (self.budget, deadline) {
+                (SearchBudget::Iterations(max_iterations), _) if iterations >= max_iterations => {
+                    break
+                }
+                (_, Some(deadline)) if Instant::now() >= deadline => break,
+                _ => {}
+            }
+
+            let mut game_runtime = determinize(view, &mut self.rng);
+            self.iterate(&mut nodes, &mut game_runtime);
+            iterations += 1;
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .map(|&child| &nodes[child])
+            .filter_map(|node| {
+                let (_, action) = node.action.unwrap();
+                legal_actions
+                    .contains(&action)
+                    .then_some((node.visits, action))
+            })
+            .max_by_key(|&(visits, _)| visits)
+            .map_or(legal_actions[0], |(_, action)| action)
+    }
+
+    fn iterate(&mut self, nodes: &mut Vec<Node>, game_runtime: &mut GameRuntime) {
+        let mut path = vec![0];
+        let mut node_idx = 0;
+        let mut legal_actions = Vec::new();
+
+        // Selection: descend while every action legal in this guess has been tried before.
+        while game_runtime.winner().is_none() {
+            let player_idx = game_runtime.current_player_idx();
+            game_runtime.legal_actions_into(&mut legal_actions);
+
+            let available_children: Vec<usize> = nodes[node_idx]
+                .children
+                .iter()
+                .copied()
+                .filter(|&child| {
+                    let (child_player_idx, action) = nodes[child].action.unwrap();
+                    child_player_idx == player_idx && legal_actions.contains(&action)
+                })
+                .collect();
+
+            for &child in &available_children {
+                nodes[child].availability += 1;
+            }
+
+            let untried_actions: Vec<Action> = legal_actions
+                .iter()
+                .copied()
+                .filter(|&action| {
+                    !available_children
+                        .iter()
+                        .any(|&child| nodes[child].action == Some((player_idx, action)))
+                })
+                .collect();
+
+            // Expansion: add one of the untried actions and stop descending.
+            if let Some(&action) = untried_actions.choose(&mut self.rng) {
+                nodes.push(Node {
+                    action: Some((player_idx, action)),
+                    children: vec![],
+                    visits: 0,
+                    wins: 0.0,
+                    availability: 1,
+                });
+                let child = nodes.len() - 1;
+                nodes[node_idx].children.push(child);
+                game_runtime.execute_action(action).unwrap();
+                path.push(child);
+                break;
+            }
+
+            let child = *available_children
+                .iter()
+                .max_by(|&&a, &&b| nodes[a].ucb().total_cmp(&nodes[b].ucb()))
+                .unwrap();
+            let (_, action) = nodes[child].action.unwrap();
+            game_runtime.execute_action(action).unwrap();
+            path.push(child);
+            node_idx = child;
+        }
+
+        let winner = self.playout(game_runtime);
+
+        // Backpropagation: every node is scored from the point of view of whoever chose it.
+        for node_idx in path {
+            let node = &mut nodes[node_idx];
+            node.visits += 1;
+
+            if let (Some((player_idx, _)), Some(winner)) = (node.action, winner) {
+                if player_idx == winner {
+                    node.wins += 1.0;
+                }
+            }
+        }
+    }
+
+    /// Plays the game out with everybody playing a card whenever they can, which is much closer to
+    /// how anybody plays than picking among every legal action, drawing included.
+    fn playout(&mut self, game_runtime: &mut GameRuntime) -> Option<usize> {
+        let mut legal_actions = Vec::new();
+
+        for _ in 0..MAX_PLAYOUT_ACTIONS {
+            if let Some(winner) = game_runtime.winner() {
+                return Some(winner);
+            }
+
+            game_runtime.legal_actions_into(&mut legal_actions);
+            if legal_actions.iter().any(is_play) {
+                legal_actions.retain(is_play);
+            }
+            let action = match legal_actions[0] {
+                Action::ChooseColor { .. } => Action::ChooseColor {
+                    color: bots::most_held_color(
+                        &game_runtime.hand(game_runtime.current_player_idx()),
+                    ),
+                },
+                _ => *legal_actions.choose(&mut self.rng).unwrap(),
+            };
+            game_runtime.execute_action(action).unwrap();
+        }
+
+        game_runtime.winner()
+    }
+}
+
+impl Agent for MctsBot {
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        if legal_actions.len() == 1 {
+            return Ok(legal_actions[0]);
+        }
+
+        Ok(self.search(view, legal_actions))
+    }
+}
+
+/// Whether `action` gets a card out of the hand, or finishes doing so.
+fn is_play(action: &Action) -> bool {
+    matches!(action, Action::Play { .. } | Action::ChooseColor { .. })
+}
+
+/// Removes one copy of every card in `to_remove` from `cards`.
+fn remove_cards(cards: &mut Vec<Card>, to_remove: &[Card]) {
+    for card in to_remove {
+        if let Some(idx) = cards.iter().position(|c| c == card) {
+            cards.swap_remove(idx);
+        }
+    }
+}
+
+/// Builds a full game consistent with what the player can see, by dealing the cards they haven't
+/// seen at random to the other hands and the drawing deck.
+pub(crate) fn determinize(view: &PlayerView, rng: &mut StdRng) -> GameRuntime {
+    let mut unseen_cards = Deck::standard().cards;
+    remove_cards(&mut unseen_cards, &view.hand);
+    remove_cards(&mut unseen_cards, view.table.discard_pile);
+    unseen_cards.shuffle(rng);
+
+    let hands: Vec<Vec<Card>> = view
+        .table
+        .hand_sizes
+        .iter()
+        .enumerate()
+        .map(|(player_idx, &hand_size)| {
+            if player_idx == view.player_idx {
+                view.hand.to_vec()
+            } else {
+                let hand_size = hand_size.min(unseen_cards.len());
+                unseen_cards.split_off(unseen_cards.len() - hand_size)
+            }
+        })
+        .collect();
+
+    let last_draw_four_was_bluff = guess_draw_four_bluff(view, &hands);
+
+    GameRuntime::from_parts(
+        view.table.ruleset,
+        view.game_state,
+        view.table.current_player_idx,
+        hands,
+        unseen_cards,
+        view.table.discard_pile.to_vec(),
+        last_draw_four_was_bluff,
+        StdRng::seed_from_u64(rng.gen()),
+    )
+}
+
+/// Who played the draw four on top of the discard pile, along with the color that was active
+/// before they did, going by what everybody saw happen since the first card was turned up.
+fn last_draw_four(view: &PlayerView) -> Option<(usize, Option<CardColor>)> {
+    // The game never starts on a wild card, and a reshuffle leaves the top card in place.
+    let mut active_color = view.table.discard_pile.first().and_then(Card::get_color);
+    let mut last_draw_four = None;
+
+    for event in view.table.events {
+        match *event {
+            Event::Played { player_idx, card } => {
+                last_draw_four = matches!(
+                    card,
+                    Card::Special(wild_card) if wild_card.kind == WildCardKind::DrawFour
+                )
+                .then_some((player_idx, active_color));
+                active_color = card.get_color();
+            }
+            Event::ChoseColor { color, .. } => active_color = Some(color),
+            _ => {}
+        }
+    }
+
+    last_draw_four
+}
+
+/// Whether the draw four on top of the discard pile was a bluff in the guessed hands, that is
+/// whether whoever played it held a card of the color that was active before it.
+fn guess_draw_four_bluff(view: &PlayerView, hands: &[Vec<Card>]) -> bool {
+    match last_draw_four(view) {
+        Some((bluffer_idx, Some(color))) => hands[bluffer_idx]
+            .iter()
+            .any(|card| card.get_color() == Some(color)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::play_game;
+    use crate::bots::bot_by_name;
+    use crate::game_state::*;
+    use crate::ruleset::Ruleset;
+
+    #[test]
+    fn determinize_keeps_public_information() {
+        let game_runtime = GameRuntime::new(3, Ruleset::default(), StdRng::seed_from_u64(11));
+        let view = PlayerView::new(&game_runtime, 0);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let guess = determinize(&view, &mut rng);
+        let guessed_view = PlayerView::new(&guess, 0);
+
+        assert_eq!(guessed_view, view);
+        assert_ne!(guess.hand(1), game_runtime.hand(1));
+    }
+
+    #[test]
+    fn draw_four_bluffs_are_judged_by_the_color_before_it() {
+        let game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(11));
+        let mut view = PlayerView::new(&game_runtime, 0);
+
+        let red_five_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Five),
+            color: CardColor::Red,
+        });
+        let wild_card = Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        });
+        let draw_four_card = Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        });
+
+        // A draw four on a wild card goes by the color chosen for the wild card.
+        let events = [
+            Event::Played {
+                player_idx: 0,
+                card: wild_card,
+            },
+            Event::ChoseColor {
+                player_idx: 0,
+                color: CardColor::Green,
+            },
+            Event::Played {
+                player_idx: 1,
+                card: draw_four_card,
+            },
+            Event::ChoseColor {
+                player_idx: 1,
+                color: CardColor::Blue,
+            },
+        ];
+        let discard_pile = [red_five_card, wild_card, draw_four_card];
+        view.table.events = &events;
+        view.table.discard_pile = &discard_pile;
+        assert_eq!(last_draw_four(&view), Some((1, Some(CardColor::Green))));
+
+        // A reshuffle that leaves nothing under the draw four doesn't lose track of it either.
+        let discard_pile = [draw_four_card];
+        view.table.discard_pile = &discard_pile;
+        assert_eq!(last_draw_four(&view), Some((1, Some(CardColor::Green))));
+
+        // Once another card is played, there's no bluff left to call.
+        let events = [
+            events[2],
+            events[3],
+            Event::Played {
+                player_idx: 0,
+                card: red_five_card,
+            },
+        ];
+        view.table.events = &events;
+        assert_eq!(last_draw_four(&view), None);
+    }
+
+    #[test]
+    fn mcts_bot_takes_the_winning_move() {
+        let blue_zero_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Zero),
+            color: CardColor::Blue,
+        });
+
+        let blue_five_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Five),
+            color: CardColor::Blue,
+        });
+
+        let game_runtime = GameRuntime::from_parts(
+            Ruleset::default(),
+            GameState {
+                last_action: Action::Play {
+                    card: blue_zero_card,
+                },
+                last_played_card: blue_zero_card,
+                last_drew_card: None,
+                chosen_color: None,
+                direction: Direction::Forward,
+            },
+            0,
+            vec![vec![blue_five_card], vec![blue_five_card; 3]],
+            Deck::standard().cards[..10].to_vec(),
+            vec![blue_zero_card],
+            false,
+            StdRng::seed_from_u64(0),
+        );
+
+        let mut bot = MctsBot::new(0, SearchBudget::Iterations(50));
+
+        assert_eq!(
+            bot.choose_action(
+                &PlayerView::new(&game_runtime, 0),
+                &game_runtime.legal_actions()
+            )
+            .unwrap(),
+            Action::Play {
+                card: blue_five_card
+            }
+        );
+    }
+
+    #[test]
+    fn mcts_bot_stops_searching_when_out_of_time() {
+        let game_runtime = GameRuntime::new(4, Ruleset::default(), StdRng::seed_from_u64(11));
+        let mut bot = MctsBot::new(0, SearchBudget::Time(Duration::from_millis(50)));
+        let started_at = Instant::now();
+
+        let action = bot
+            .choose_action(
+                &PlayerView::new(&game_runtime, 0),
+                &game_runtime.legal_actions(),
+            )
+            .unwrap();
+
+        assert!(game_runtime.legal_actions().contains(&action));
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn mcts_bot_wins_more_often_than_the_heuristic_bot() {
+        let deals = 10;
+        let mut mcts_wins = 0;
+
+        // Each deal is played twice, so that both bots get to go first.
+        for seed in 0..deals {
+            for mcts_seat in 0..2 {
+                let mut game_runtime =
+                    GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(seed));
+                let mut agents: Vec<Box<dyn Agent>> = vec![
+                    bot_by_name("heuristic", seed).unwrap(),
+                    Box::new(MctsBot::new(seed, SearchBudget::Iterations(1000))),
+                ];
+                agents.rotate_left(1 - mcts_seat);
+
+                if play_game(&mut game_runtime, &mut agents).unwrap() == mcts_seat {
+                    mcts_wins += 1;
+                }
+            }
+        }
+
+        assert!(mcts_wins > deals, "mcts won {} of {}", mcts_wins, 2 * deals);
+    }
+
+    #[test]
+    fn mcts_bot_plays_a_complete_game() {
+        let mut game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(5));
+        let mut agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(MctsBot::new(0, SearchBudget::Iterations(20))),
+            bot_by_name("random", 0).unwrap(),
+        ];
+
+        assert!(play_game(&mut game_runtime, &mut agents).is_ok());
+    }
+}
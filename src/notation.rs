@@ -0,0 +1,191 @@
+use crate::game_executor::Event;
+use crate::game_state::{
+    Action, Card, CardColor, CardKind, DrawAmount, NormalCard, Numeric, WildCard, WildCardKind,
+};
+
+const DRAW_AMOUNTS: [DrawAmount; 4] = [
+    DrawAmount::One,
+    DrawAmount::Two,
+    DrawAmount::Four,
+    DrawAmount::Six,
+];
+
+pub(crate) fn format_color(color: CardColor) -> &'static str {
+    match color {
+        CardColor::Blue => "blue",
+        CardColor::Green => "green",
+        CardColor::Yellow => "yellow",
+        CardColor::Red => "red",
+    }
+}
+
+pub(crate) fn parse_color(text: &str) -> Option<CardColor> {
+    CardColor::ALL
+        .into_iter()
+        .find(|&color| format_color(color) == text)
+}
+
+/// Numeric cards are their color's initial followed by their value (`r5`, `g0`), the wild card is
+/// `w` and the wild draw four is `w4`.
+pub(crate) fn format_card(card: Card) -> String {
+    match card {
+        Card::Normal(NormalCard {
+            kind: CardKind::Numeric(numeric),
+            color,
+        }) => format!("{}{}", &format_color(color)[..1], numeric.value()),
+        Card::Special(WildCard {
+            kind: WildCardKind::Colorchooser,
+        }) => "w".to_owned(),
+        Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        }) => "w4".to_owned(),
+    }
+}
+
+pub(crate) fn parse_card(text: &str) -> Option<Card> {
+    match text {
+        "w" => {
+            return Some(Card::Special(WildCard {
+                kind: WildCardKind::Colorchooser,
+            }))
+        }
+        "w4" => {
+            return Some(Card::Special(WildCard {
+                kind: WildCardKind::DrawFour,
+            }))
+        }
+        _ => {}
+    }
+
+    let mut chars = text.chars();
+    let (Some(initial), Some(digit), None) = (chars.next(), chars.next(), chars.next()) else {
+        return None;
+    };
+
+    let color = CardColor::ALL
+        .into_iter()
+        .find(|&color| format_color(color).starts_with(initial))?;
+    let numeric = Numeric::ALL[digit.to_digit(10)? as usize];
+
+    Some(Card::Normal(NormalCard {
+        kind: CardKind::Numeric(numeric),
+        color,
+    }))
+}
+
+/// Playing a card is written as the card itself. The other actions are a color name (`red`),
+/// `draw1`, `draw4` and so on, `bluff` and `pass`.
+pub(crate) fn format_action(action: Action) -> String {
+    match action {
+        Action::Play { card } => format_card(card),
+        Action::ChooseColor { color } => format_color(color).to_owned(),
+        Action::DrawCard { amount } => format!("draw{}", amount.count()),
+        Action::CallBluff => "bluff".to_owned(),
+        Action::Pass => "pass".to_owned(),
+    }
+}
+
+pub(crate) fn parse_action(text: &str) -> Option<Action> {
+    match text {
+        "bluff" => return Some(Action::CallBluff),
+        "pass" => return Some(Action::Pass),
+        _ => {}
+    }
+
+    if let Some(count) = text.strip_prefix("draw") {
+        return DRAW_AMOUNTS
+            .into_iter()
+            .find(|amount| amount.count().to_string() == count)
+            .map(|amount| Action::DrawCard { amount });
+    }
+
+    if let Some(color) = parse_color(text) {
+        return Some(Action::ChooseColor { color });
+    }
+
+    parse_card(text).map(|card| Action::Play { card })
+}
+
+/// An event as the words of a line, e.g. `played 0 r5` or `drew 1 4`. Players are given by seat.
+pub(crate) fn format_event(event: Event) -> String {
+    match event {
+        Event::Played { player_idx, card } => {
+            format!("played {} {}", player_idx, format_card(card))
+        }
+        Event::ChoseColor { player_idx, color } => {
+            format!("chose {} {}", player_idx, format_color(color))
+        }
+        Event::Drew { player_idx, amount } => format!("drew {} {}", player_idx, amount),
+        Event::CalledBluff {
+            player_idx,
+            succeeded,
+        } => format!(
+            "bluff {} {}",
+            player_idx,
+            if succeeded { "succeeded" } else { "failed" }
+        ),
+        Event::Passed { player_idx } => format!("passed {}", player_idx),
+        Event::ReshuffledDiscardPile => "reshuffled".to_owned(),
+        Event::Won { player_idx } => format!("won {}", player_idx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::Deck;
+
+    #[test]
+    fn every_card_and_action_round_trips() {
+        for card in Deck::standard().cards {
+            assert_eq!(parse_card(&format_card(card)), Some(card));
+            assert_eq!(
+                parse_action(&format_action(Action::Play { card })),
+                Some(Action::Play { card })
+            );
+        }
+
+        let mut actions = vec![Action::CallBluff, Action::Pass];
+        actions.extend(
+            CardColor::ALL
+                .into_iter()
+                .map(|color| Action::ChooseColor { color }),
+        );
+        actions.extend(
+            DRAW_AMOUNTS
+                .into_iter()
+                .map(|amount| Action::DrawCard { amount }),
+        );
+        for action in actions {
+            assert_eq!(parse_action(&format_action(action)), Some(action));
+        }
+
+        for text in ["", "x5", "r", "r10", "draw3", "w5", "blue4"] {
+            assert_eq!(parse_action(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn cards_and_events_read_naturally() {
+        let card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::Five),
+            color: CardColor::Yellow,
+        });
+
+        assert_eq!(format_card(card), "y5");
+        assert_eq!(
+            format_event(Event::Played {
+                player_idx: 2,
+                card
+            }),
+            "played 2 y5"
+        );
+        assert_eq!(
+            format_event(Event::CalledBluff {
+                player_idx: 0,
+                succeeded: false
+            }),
+            "bluff 0 failed"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use alloc::vec::Vec;
+
+use crate::game_state::{Card, CardColor, CardKind, NormalCard, Numeric, WildCard, WildCardKind};
+
+/// How many distinct cards there are, and so how many bits of a card type set are used.
+pub(crate) const NUM_CARD_TYPES: usize = 42;
+
+const NUM_NUMERICS: usize = 10;
+const WILD_INDEX: u8 = 40;
+const DRAW_FOUR_INDEX: u8 = 41;
+
+/// Every card type of each color, by `CardColor` discriminant.
+const COLOR_MASKS: [u64; 4] = {
+    let mut masks = [0; 4];
+    let mut color = 0;
+    while color < 4 {
+        masks[color] = ((1 << NUM_NUMERICS) - 1) << (color * NUM_NUMERICS);
+        color += 1;
+    }
+    masks
+};
+
+/// Every card type of each numeric, one per color.
+const NUMERIC_MASKS: [u64; NUM_NUMERICS] = {
+    let mut masks = [0; NUM_NUMERICS];
+    let mut value = 0;
+    while value < NUM_NUMERICS {
+        let mut color = 0;
+        while color < 4 {
+            masks[value] |= 1 << (color * NUM_NUMERICS + value);
+            color += 1;
+        }
+        value += 1;
+    }
+    masks
+};
+
+const WILD_MASK: u64 = 1 << WILD_INDEX | 1 << DRAW_FOUR_INDEX;
+
+/// What can be played on top of each numeric card: its color, its numeric and the wild cards.
+const PLAYABLE_ON_NUMERIC: [u64; NUM_CARD_TYPES - 2] = {
+    let mut masks = [0; NUM_CARD_TYPES - 2];
+    let mut idx = 0;
+    while idx < NUM_CARD_TYPES - 2 {
+        masks[idx] =
+            COLOR_MASKS[idx / NUM_NUMERICS] | NUMERIC_MASKS[idx % NUM_NUMERICS] | WILD_MASK;
+        idx += 1;
+    }
+    masks
+};
+
+/// A card as a single byte: `color * 10 + numeric` for numeric cards, then 40 for the wild card
+/// and 41 for the wild draw four. Doubles as the card's index among `NUM_CARD_TYPES`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub(crate) struct PackedCard(u8);
+
+impl PackedCard {
+    pub(crate) fn new(card: Card) -> PackedCard {
+        PackedCard(match card {
+            Card::Normal(NormalCard {
+                kind: CardKind::Numeric(numeric),
+                color,
+            }) => color as u8 * NUM_NUMERICS as u8 + numeric.value(),
+            Card::Special(WildCard {
+                kind: WildCardKind::Colorchooser,
+            }) => WILD_INDEX,
+            Card::Special(WildCard {
+                kind: WildCardKind::DrawFour,
+            }) => DRAW_FOUR_INDEX,
+        })
+    }
+
+    pub(crate) fn from_index(idx: usize) -> Option<PackedCard> {
+        (idx < NUM_CARD_TYPES).then_some(PackedCard(idx as u8))
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The card's bit in card type sets.
+    pub(crate) fn bit(self) -> u64 {
+        1 << self.0
+    }
+
+    pub(crate) fn unpack(self) -> Card {
+        match self.0 {
+            WILD_INDEX => Card::Special(WildCard {
+                kind: WildCardKind::Colorchooser,
+            }),
+            DRAW_FOUR_INDEX => Card::Special(WildCard {
+                kind: WildCardKind::DrawFour,
+            }),
+            idx => Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::ALL[idx as usize % NUM_NUMERICS]),
+                color: CardColor::ALL[idx as usize / NUM_NUMERICS],
+            }),
+        }
+    }
+}
+
+/// The set of card types that may be played on `top_card`. Once a wild card is down, only cards
+/// of the chosen color are, along with other wild cards unless it was a draw four, and nothing is
+/// until the color is chosen.
+pub(crate) fn playable_mask(top_card: Card, chosen_color: Option<CardColor>) -> u64 {
+    let top_card = PackedCard::new(top_card);
+
+    match (PLAYABLE_ON_NUMERIC.get(top_card.index()), chosen_color) {
+        (Some(&mask), _) => mask,
+        (None, Some(color)) if top_card.0 == DRAW_FOUR_INDEX => COLOR_MASKS[color as usize],
+        (None, Some(color)) => COLOR_MASKS[color as usize] | WILD_MASK,
+        (None, None) => 0,
+    }
+}
+
+/// The set of card types of `color`.
+pub(crate) fn color_mask(color: CardColor) -> u64 {
+    COLOR_MASKS[color as usize]
+}
+
+/// A hand as how many cards of each type it holds, so that finding, adding and removing cards
+/// takes constant time and matching it against a set of card types takes a single AND.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub(crate) struct PackedHand {
+    counts: [u8; NUM_CARD_TYPES],
+    /// The types the hand holds at least one card of.
+    present: u64,
+    len: usize,
+}
+
+impl Default for PackedHand {
+    fn default() -> PackedHand {
+        PackedHand {
+            counts: [0; NUM_CARD_TYPES],
+            present: 0,
+            len: 0,
+        }
+    }
+}
+
+impl PackedHand {
+    pub(crate) fn from_cards(cards: &[Card]) -> PackedHand {
+        let mut hand = PackedHand::default();
+        for &card in cards {
+            hand.insert(PackedCard::new(card));
+        }
+        hand
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn count(&self, card: PackedCard) -> usize {
+        self.counts[card.index()] as usize
+    }
+
+    /// The types the hand holds at least one card of.
+    pub(crate) fn present(&self) -> u64 {
+        self.present
+    }
+
+    pub(crate) fn insert(&mut self, card: PackedCard) {
+        self.counts[card.index()] += 1;
+        self.present |= card.bit();
+        self.len += 1;
+    }
+
+    /// Takes one card of the type out of the hand, returning whether there was one.
+    pub(crate) fn remove(&mut self, card: PackedCard) -> bool {
+        let count = &mut self.counts[card.index()];
+        if *count == 0 {
+            return false;
+        }
+
+        *count -= 1;
+        self.present &= !(card.bit() * (*count == 0) as u64);
+        self.len -= 1;
+        true
+    }
+
+    /// Each type the hand holds, in type order, with how many of it.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (PackedCard, usize)> + '_ {
+        SetBits(self.present).map(|card| (card, self.count(card)))
+    }
+
+    /// Every card of the hand, sorted by type.
+    pub(crate) fn cards(&self) -> Vec<Card> {
+        let mut cards = Vec::with_capacity(self.len);
+        for (card, count) in self.iter() {
+            cards.extend(core::iter::repeat_n(card.unpack(), count));
+        }
+        cards
+    }
+}
+
+/// The card types in a set, lowest first.
+pub(crate) struct SetBits(pub(crate) u64);
+
+impl Iterator for SetBits {
+    type Item = PackedCard;
+
+    fn next(&mut self) -> Option<PackedCard> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let idx = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+        Some(PackedCard(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::Deck;
+
+    #[test]
+    fn every_card_packs_into_its_own_byte() {
+        let mut seen = [false; NUM_CARD_TYPES];
+
+        for card in Deck::standard().cards {
+            let packed = PackedCard::new(card);
+            assert_eq!(packed.unpack(), card);
+            seen[packed.index()] = true;
+        }
+
+        assert!(seen.iter().all(|&seen| seen));
+        assert_eq!(PackedCard::from_index(NUM_CARD_TYPES), None);
+    }
+
+    #[test]
+    fn playable_mask_agrees_with_the_rules() {
+        let deck = Deck::standard().cards;
+
+        for &top_card in &deck {
+            for chosen_color in [None, Some(CardColor::Red), Some(CardColor::Blue)] {
+                // Only a wild card on top has a chosen color.
+                let chosen_color = top_card.get_color().map_or(chosen_color, |_| None);
+                let mask = playable_mask(top_card, chosen_color);
+
+                for &card in &deck {
+                    let expected = match (top_card, card) {
+                        (Card::Normal(played), Card::Normal(to_play)) => {
+                            played.kind == to_play.kind || played.color == to_play.color
+                        }
+                        (Card::Normal(_), Card::Special(_)) => true,
+                        (Card::Special(_), Card::Normal(to_play)) => {
+                            chosen_color == Some(to_play.color)
+                        }
+                        // Nothing but the chosen color goes on a draw four.
+                        (Card::Special(played), Card::Special(_)) => {
+                            chosen_color.is_some() && played.kind != WildCardKind::DrawFour
+                        }
+                    };
+
+                    assert_eq!(mask & PackedCard::new(card).bit() != 0, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn packed_hands_count_cards_by_type() {
+        let deck = Deck::standard().cards;
+        let mut hand = PackedHand::from_cards(&deck[..10]);
+        let card = PackedCard::new(deck[1]);
+
+        // The first ten cards are blue: one zero, then two of each numeric up to five.
+        assert_eq!(hand.len, 10);
+        assert_eq!(hand.count(card), 2);
+        assert_eq!(hand.present() & !color_mask(CardColor::Blue), 0);
+        assert_eq!(hand.iter().count(), 6);
+
+        assert!(hand.remove(card));
+        assert!(hand.remove(card));
+        assert!(!hand.remove(card));
+        assert_eq!(hand.present() & card.bit(), 0);
+        assert_eq!(hand.len, 8);
+
+        hand.insert(card);
+        assert_eq!(hand.iter().nth(1), Some((card, 1)));
+    }
+}
@@ -0,0 +1,418 @@
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::game_executor::GameRuntime;
+use crate::game_state::{
+    Action, Card, Deck, Direction, DrawAmount, GameState, WildCard, WildCardKind,
+};
+use crate::notation;
+use crate::packed::{PackedCard, NUM_CARD_TYPES};
+use crate::ruleset::Ruleset;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct PositionError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// A game frozen at some point, from which every line of play can be enumerated.
+///
+/// Positions are written one field per line, with cards and actions in the engine protocol's
+/// notation and `#` starting a comment:
+///
+/// ```text
+/// ruleset standard
+/// hand r5 g2 w      # one line per seat, in seat order
+/// hand b1 y7
+/// discard g3 r3     # bottom to top, so r3 is the top card
+/// deck r1 w4 b2     # in the order the cards are drawn
+/// turn 0
+/// ```
+///
+/// `direction backward`, `color <color>` for a wild card on top, `last <action>` and
+/// `drew <card>` for a player who just drew, `bluff` when the draw four on top was one, and
+/// `seed <number>` for reshuffling the discard pile are optional. Without `last`, the top card
+/// was just played, or its color chosen if there is one.
+#[derive(Clone)]
+pub struct Position {
+    game_runtime: GameRuntime,
+    /// Reused by `legal_action_count`.
+    actions: Vec<Action>,
+}
+
+impl Position {
+    /// A freshly dealt game.
+    pub fn dealt(num_players: usize, ruleset: Ruleset, seed: u64) -> Position {
+        Position::new(GameRuntime::new(
+            num_players,
+            ruleset,
+            StdRng::seed_from_u64(seed),
+        ))
+    }
+
+    pub fn parse(text: &str) -> Result<Position, PositionError> {
+        let mut ruleset = Ruleset::default();
+        let mut hands = vec![];
+        let mut discard_pile = vec![];
+        let mut drawing_deck = vec![];
+        let mut current_player_idx = 0;
+        let mut direction = Direction::Forward;
+        let mut chosen_color = None;
+        let mut last_action = None;
+        let mut last_drew_card = None;
+        let mut last_draw_four_was_bluff = false;
+        let mut seed = 0;
+
+        for (idx, line) in text.lines().enumerate() {
+            let error = |message: String| PositionError {
+                line: idx + 1,
+                message,
+            };
+            let parse_cards = |words: &[&str]| {
+                words
+                    .iter()
+                    .map(|&word| {
+                        notation::parse_card(word)
+                            .ok_or_else(|| error(format!("bad card {}", word)))
+                    })
+                    .collect::<Result<Vec<Card>, _>>()
+            };
+
+            let line = line.split('#').next().unwrap_or("");
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let Some((&field, values)) = words.split_first() else {
+                continue;
+            };
+
+            match (field, values) {
+                ("ruleset", [name]) => {
+                    ruleset = Ruleset::by_name(name)
+                        .ok_or_else(|| error(format!("unknown ruleset {}", name)))?;
+                }
+                ("hand", cards) => hands.push(parse_cards(cards)?),
+                ("discard", cards) => discard_pile = parse_cards(cards)?,
+                ("deck", cards) => {
+                    drawing_deck = parse_cards(cards)?;
+                    // The drawing deck is drawn from its end.
+                    drawing_deck.reverse();
+                }
+                ("turn", [seat]) => {
+                    current_player_idx = seat
+                        .parse()
+                        .map_err(|_| error(format!("bad seat {}", seat)))?;
+                }
+                ("direction", ["forward"]) => direction = Direction::Forward,
+                ("direction", ["backward"]) => direction = Direction::Backward,
+                ("color", [color]) => {
+                    chosen_color = Some(
+                        notation::parse_color(color)
+                            .ok_or_else(|| error(format!("bad color {}", color)))?,
+                    );
+                }
+                ("last", [action]) => {
+                    last_action = Some(
+                        notation::parse_action(action)
+                            .ok_or_else(|| error(format!("bad action {}", action)))?,
+                    );
+                }
+                ("drew", [card]) => last_drew_card = Some(parse_cards(&[card])?[0]),
+                ("bluff", []) => last_draw_four_was_bluff = true,
+                ("seed", [number]) => {
+                    seed = number
+                        .parse()
+                        .map_err(|_| error(format!("bad seed {}", number)))?;
+                }
+                _ => return Err(error(format!("unexpected {}", line.trim()))),
+            }
+        }
+
+        let error = |message: &str| PositionError {
+            line: text.lines().count(),
+            message: message.to_owned(),
+        };
+
+        let &last_played_card = discard_pile
+            .last()
+            .ok_or_else(|| error("no discard pile"))?;
+        if hands.len() < 2 {
+            return Err(error("a game needs at least two hands"));
+        }
+        if current_player_idx >= hands.len() {
+            return Err(error("the turn belongs to a seat without a hand"));
+        }
+        if hands.iter().any(Vec::is_empty) {
+            return Err(error("a seat without cards would have won already"));
+        }
+
+        let last_action = last_action.unwrap_or(match chosen_color {
+            Some(color) => Action::ChooseColor { color },
+            None => Action::Play {
+                card: last_played_card,
+            },
+        });
+
+        // Only states the game itself could get into are accepted, since the rest are never
+        // checked for.
+        let top_is_wild = last_played_card.get_color().is_none();
+        match last_action {
+            Action::Play { card } if card != last_played_card => {
+                return Err(error("the last card played has to be the top card"));
+            }
+            Action::Play { .. } if chosen_color.is_some() => {
+                return Err(error("a color was chosen after the last card played"));
+            }
+            Action::ChooseColor { color } if chosen_color != Some(color) => {
+                return Err(error("the last color chosen has to be the active color"));
+            }
+            _ if top_is_wild == chosen_color.is_none()
+                && !matches!(last_action, Action::Play { .. }) =>
+            {
+                return Err(error("only a wild card on top has a chosen color"));
+            }
+            _ => {}
+        }
+        if last_drew_card.is_some()
+            && last_action
+                != (Action::DrawCard {
+                    amount: DrawAmount::One,
+                })
+        {
+            return Err(error("a card can only have been drawn by drawing one"));
+        }
+        if last_drew_card.is_some_and(|card| !hands[current_player_idx].contains(&card)) {
+            return Err(error(
+                "the card just drawn has to be in the hand of whoever drew it",
+            ));
+        }
+        if last_draw_four_was_bluff
+            && last_played_card
+                != Card::Special(WildCard {
+                    kind: WildCardKind::DrawFour,
+                })
+        {
+            return Err(error("only a draw four can have been a bluff"));
+        }
+
+        let mut remaining = [0usize; NUM_CARD_TYPES];
+        for card in Deck::standard().cards {
+            remaining[PackedCard::new(card).index()] += 1;
+        }
+        for &card in hands
+            .iter()
+            .flatten()
+            .chain(&discard_pile)
+            .chain(&drawing_deck)
+        {
+            let count = &mut remaining[PackedCard::new(card).index()];
+            if *count == 0 {
+                return Err(error(&format!(
+                    "there are more {} than the deck has",
+                    notation::format_card(card)
+                )));
+            }
+            *count -= 1;
+        }
+
+        Ok(Position::new(GameRuntime::from_parts(
+            ruleset,
+            GameState {
+                last_action,
+                last_played_card,
+                last_drew_card,
+                chosen_color,
+                direction,
+            },
+            current_player_idx,
+            hands,
+            drawing_deck,
+            discard_pile,
+            last_draw_four_was_bluff,
+            StdRng::seed_from_u64(seed),
+        )))
+    }
+
+    fn new(game_runtime: GameRuntime) -> Position {
+        Position {
+            game_runtime,
+            actions: vec![],
+        }
+    }
+
+    /// Counts the lines of play exactly `depth` actions long. Games that end sooner don't count.
+    pub fn perft(&self, depth: usize) -> u64 {
+        let mut buffers = vec![vec![]; depth];
+        perft(&self.game_runtime, &mut buffers)
+    }
+
+    /// The `perft` count under each legal action, written in notation, for tracking down which
+    /// line a miscount is in.
+    pub fn divide(&self, depth: usize) -> Vec<(String, u64)> {
+        assert!(depth > 0, "dividing needs at least one action");
+
+        let mut buffers = vec![vec![]; depth - 1];
+        self.game_runtime
+            .legal_actions()
+            .into_iter()
+            .map(|action| {
+                let child = child(&self.game_runtime, action);
+                (notation::format_action(action), perft(&child, &mut buffers))
+            })
+            .collect()
+    }
+
+    pub fn legal_action_count(&mut self) -> usize {
+        self.game_runtime.legal_actions_into(&mut self.actions);
+        self.actions.len()
+    }
+
+    /// Plays the last legal action until the game is over, which never just draws and passes
+    /// while a card can be played, and returns how many actions it took.
+    pub fn play_out(&mut self) -> usize {
+        let mut actions = 0;
+
+        while self.game_runtime.winner().is_none() {
+            self.game_runtime.legal_actions_into(&mut self.actions);
+            let action = *self.actions.last().unwrap();
+            self.game_runtime = child(&self.game_runtime, action);
+            actions += 1;
+        }
+
+        actions
+    }
+}
+
+fn child(game_runtime: &GameRuntime, action: Action) -> GameRuntime {
+    let mut child = game_runtime.clone();
+    child
+        .execute_action(action)
+        .unwrap_or_else(|err| panic!("generated action was rejected: {}", err));
+    child
+}
+
+/// Counts the lines of play as deep as there are `buffers`, one for each level's legal actions.
+fn perft(game_runtime: &GameRuntime, buffers: &mut [Vec<Action>]) -> u64 {
+    let Some((actions, deeper_buffers)) = buffers.split_first_mut() else {
+        return 1;
+    };
+
+    game_runtime.legal_actions_into(actions);
+
+    // The last level needs no executing, only counting.
+    if deeper_buffers.is_empty() {
+        return actions.len() as u64;
+    }
+
+    actions
+        .iter()
+        .map(|&action| perft(&child(game_runtime, action), deeper_buffers))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two players with two cards each, small enough to count by hand.
+    const TINY: &str = "
+        hand r5 w
+        hand r7 b5
+        discard r3
+        deck g1 g2 g3
+    ";
+
+    /// A wild card on top, just after its color was chosen.
+    const AFTER_WILD: &str = "
+        ruleset forced-play
+        hand b1 b2 g5 w4 r9
+        hand y1 y2 w b9
+        hand r1 g1 b4
+        discard y3 w
+        color blue
+        deck r2 y5 g7 b3 w r4 y9 g0
+        turn 1
+        direction backward
+    ";
+
+    #[test]
+    fn tiny_position_counts_by_hand() {
+        let position = Position::parse(TINY).unwrap();
+
+        // Seat 0 can draw, play r5 or play w.
+        assert_eq!(position.perft(1), 3);
+        // Drawing g1 leaves only passing, r5 gives seat 1 a draw, r7 and b5, and the wild card
+        // gives seat 0 the four colors.
+        assert_eq!(position.perft(2), 1 + 3 + 4);
+        assert_eq!(
+            position.divide(2),
+            [
+                ("draw1".to_owned(), 1),
+                ("r5".to_owned(), 3),
+                ("w".to_owned(), 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn reference_counts() {
+        let position = Position::parse(TINY).unwrap();
+        let counts: Vec<u64> = (1..=6).map(|depth| position.perft(depth)).collect();
+        assert_eq!(counts, [3, 8, 13, 17, 21, 30]);
+
+        let position = Position::parse(AFTER_WILD).unwrap();
+        let counts: Vec<u64> = (1..=5).map(|depth| position.perft(depth)).collect();
+        assert_eq!(counts, [3, 10, 29, 67, 137]);
+
+        let position = Position::dealt(3, Ruleset::default(), 7);
+        let counts: Vec<u64> = (1..=5).map(|depth| position.perft(depth)).collect();
+        assert_eq!(counts, [3, 11, 30, 84, 246]);
+    }
+
+    #[test]
+    fn malformed_positions_are_rejected() {
+        assert!(Position::parse("hand r5\nhand r7\n").is_err());
+        assert!(Position::parse("hand r5\ndiscard r3\n").is_err());
+        assert!(Position::parse("hand r5\nhand r7\ndiscard r3\ncolor red\n").is_err());
+        assert!(Position::parse("hand r5\nhand r7\ndiscard r3\nturn 2\n").is_err());
+        // States the game can't get into would make it offer actions it can't carry out.
+        assert!(Position::parse("hand r5\nhand r7\ndiscard r3\nlast red\n").is_err());
+        assert!(Position::parse("hand r5\nhand r7\ndiscard w\nlast pass\n").is_err());
+        assert!(Position::parse("hand r5\nhand r7\ndiscard r3\nlast draw1\ndrew r9\n").is_err());
+
+        // No more copies of a card than the deck has.
+        let err = Position::parse("hand r5 r5\nhand r5\ndiscard r3\n")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 3: there are more r5 than the deck has"
+        );
+        assert!(Position::parse("hand r0\nhand r7\ndiscard r0\n").is_err());
+        assert!(Position::parse("hand w4 w4\nhand w4\ndiscard r3\ndeck w4 w4\n").is_err());
+        assert!(Position::parse(&format!(
+            "hand {}\nhand r7\ndiscard r3\n",
+            ["r5"; 300].join(" ")
+        ))
+        .is_err());
+
+        let err = Position::parse("hand r5\nhand x7\ndiscard r3\n")
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "line 2: bad card x7");
+    }
+
+    #[test]
+    fn playing_out_finishes_the_game() {
+        let mut position = Position::dealt(4, Ruleset::default(), 3);
+        assert!(position.legal_action_count() > 0);
+        assert!(position.play_out() > 0);
+        assert_eq!(position.legal_action_count(), 0);
+    }
+}
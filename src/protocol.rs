@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_executor::Event;
+use crate::game_state::{Action, Card, CardColor};
+use crate::view::{OmniscientView, PlayerView, SpectatorView};
+
+/// Everything a seat gets to see, in a form that can be sent over the wire.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct TableView {
+    pub(crate) seat: usize,
+    pub(crate) hand: Vec<Card>,
+    /// The card this seat just drew, if it may still play it.
+    pub(crate) drawn_card: Option<Card>,
+    pub(crate) top_card: Card,
+    pub(crate) active_color: Option<CardColor>,
+    pub(crate) current_player_idx: usize,
+    pub(crate) hand_sizes: Vec<usize>,
+    pub(crate) drawing_deck_len: usize,
+    pub(crate) winner: Option<usize>,
+}
+
+impl TableView {
+    pub(crate) fn new(view: &PlayerView) -> TableView {
+        TableView {
+            seat: view.player_idx,
+            hand: view.hand.to_vec(),
+            drawn_card: view.drawn_card(),
+            top_card: view.table.top_card(),
+            active_color: view.table.active_color,
+            current_player_idx: view.table.current_player_idx,
+            hand_sizes: view.table.hand_sizes.clone(),
+            drawing_deck_len: view.table.drawing_deck_len,
+            winner: view.table.winner,
+        }
+    }
+}
+
+/// What spectators get to see. Only omniscient spectators are shown the hands.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SpectatorTableView {
+    pub(crate) top_card: Card,
+    pub(crate) active_color: Option<CardColor>,
+    pub(crate) current_player_idx: usize,
+    pub(crate) hand_sizes: Vec<usize>,
+    pub(crate) drawing_deck_len: usize,
+    pub(crate) winner: Option<usize>,
+    pub(crate) hands: Option<Vec<Vec<Card>>>,
+}
+
+impl SpectatorTableView {
+    pub(crate) fn new(view: &SpectatorView) -> SpectatorTableView {
+        SpectatorTableView {
+            top_card: view.top_card(),
+            active_color: view.active_color,
+            current_player_idx: view.current_player_idx,
+            hand_sizes: view.hand_sizes.clone(),
+            drawing_deck_len: view.drawing_deck_len,
+            winner: view.winner,
+            hands: None,
+        }
+    }
+
+    pub(crate) fn omniscient(view: &OmniscientView) -> SpectatorTableView {
+        SpectatorTableView {
+            hands: Some(view.hands.iter().map(|hand| hand.to_vec()).collect()),
+            ..SpectatorTableView::new(&view.table)
+        }
+    }
+}
+
+/// Who sits in a seat of a room, as shown in room listings.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SeatSummary {
+    pub(crate) name: String,
+    pub(crate) bot: bool,
+    pub(crate) ready: bool,
+    /// The player dropped out mid-game, and may still come back.
+    pub(crate) away: bool,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct RoomSummary {
+    pub(crate) room_id: usize,
+    pub(crate) ruleset: String,
+    /// `None` for empty seats.
+    pub(crate) seats: Vec<Option<SeatSummary>>,
+    pub(crate) spectators: usize,
+    pub(crate) playing: bool,
+}
+
+/// A player's results over every game finished in the lobby.
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct PlayerScore {
+    pub(crate) name: String,
+    pub(crate) games: usize,
+    pub(crate) wins: usize,
+    /// The value of the cards left in the opponents' hands, summed over every game won.
+    pub(crate) points: u32,
+}
+
+/// A line sent by a client.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ClientMessage {
+    /// Sets the name shown to other players and used to keep score.
+    SetName {
+        name: String,
+    },
+    ListRooms,
+    /// Creates a room and takes its first seat.
+    CreateRoom {
+        ruleset: String,
+        seats: usize,
+    },
+    /// Takes the given seat of a room, or the first free one.
+    JoinRoom {
+        room_id: usize,
+        seat: Option<usize>,
+    },
+    /// Watches a room without taking a seat.
+    Spectate {
+        room_id: usize,
+        /// Shows every hand, but only after the server's delay.
+        #[serde(default)]
+        omniscient: bool,
+    },
+    LeaveRoom,
+    /// Takes back a seat after reconnecting, using the token sent on joining.
+    Resume {
+        token: String,
+    },
+    /// The game starts once every seat is taken and every human in the room is ready.
+    Ready {
+        ready: bool,
+    },
+    FillWithBots {
+        bot: String,
+    },
+    Action {
+        action: Action,
+    },
+    Scores,
+}
+
+/// A line sent by the server.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ServerMessage {
+    Rooms {
+        rooms: Vec<RoomSummary>,
+    },
+    Joined {
+        room_id: usize,
+        seat: usize,
+        /// Sent with `resume` to get the seat back after losing the connection.
+        token: String,
+    },
+    /// Followed by a `state` message with the current view of the game.
+    Resumed {
+        room_id: usize,
+        seat: usize,
+        missed_events: Vec<Event>,
+    },
+    Spectating {
+        room_id: usize,
+        omniscient: bool,
+        /// How far behind the game the omniscient view is.
+        delay_seconds: u64,
+    },
+    Left {
+        room_id: usize,
+    },
+    /// Sent to everybody in a room whenever its seats change.
+    Room {
+        room: RoomSummary,
+    },
+    /// Sent to every seat whenever the game changes. Only the current player gets legal actions.
+    State {
+        view: TableView,
+        legal_actions: Vec<Action>,
+    },
+    /// Sent to spectators whenever the game changes.
+    Table {
+        view: SpectatorTableView,
+    },
+    Event {
+        event: Event,
+    },
+    /// The seat ran out of time, and the server is playing its turn for it.
+    TimedOut {
+        seat: usize,
+    },
+    GameOver {
+        winner: usize,
+        hands: Vec<Vec<Card>>,
+        points: u32,
+    },
+    Scores {
+        scores: Vec<PlayerScore>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::{CardKind, NormalCard, Numeric};
+
+    #[test]
+    fn messages_are_tagged_json_objects() {
+        let message = ClientMessage::Action {
+            action: Action::Play {
+                card: Card::Normal(NormalCard {
+                    kind: CardKind::Numeric(Numeric::Five),
+                    color: CardColor::Red,
+                }),
+            },
+        };
+
+        let line = serde_json::to_string(&message).unwrap();
+
+        assert_eq!(
+            line,
+            r#"{"type":"action","action":{"play":{"card":{"normal":{"kind":{"numeric":"five"},"color":"red"}}}}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(&line).unwrap(),
+            message
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(
+                r#"{"type":"join_room","room_id":1,"seat":null}"#
+            )
+            .unwrap(),
+            ClientMessage::JoinRoom {
+                room_id: 1,
+                seat: None
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(r#"{"type":"list_rooms"}"#).unwrap(),
+            ClientMessage::ListRooms
+        );
+    }
+}
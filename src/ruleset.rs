@@ -0,0 +1,51 @@
+/// House rules that change how a game is dealt and which actions are legal.
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Ruleset {
+    pub initial_hand_size: usize,
+    /// Whether a player hit by a draw four may challenge it instead of drawing.
+    pub bluff_challenges: bool,
+    /// Whether a player who draws a playable card has to play it instead of passing.
+    pub forced_play_after_draw: bool,
+}
+
+impl Ruleset {
+    pub const NAMES: [&'static str; 4] = ["standard", "no-bluffing", "forced-play", "quick"];
+
+    pub const STANDARD: Ruleset = Ruleset {
+        initial_hand_size: 7,
+        bluff_challenges: true,
+        forced_play_after_draw: false,
+    };
+
+    pub fn by_name(name: &str) -> Option<Ruleset> {
+        match name {
+            "standard" => Some(Ruleset::STANDARD),
+            "no-bluffing" => Some(Ruleset {
+                bluff_challenges: false,
+                ..Ruleset::STANDARD
+            }),
+            "forced-play" => Some(Ruleset {
+                forced_play_after_draw: true,
+                ..Ruleset::STANDARD
+            }),
+            "quick" => Some(Ruleset {
+                initial_hand_size: 5,
+                ..Ruleset::STANDARD
+            }),
+            _ => None,
+        }
+    }
+
+    /// The most players that can be dealt a hand while surely leaving a numeric card to start
+    /// the game with, out of the 84 cards of the deck and its eight wild cards.
+    pub fn max_players(&self) -> usize {
+        (84 - 8 - 1) / self.initial_hand_size.max(1)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Ruleset {
+        Ruleset::STANDARD
+    }
+}
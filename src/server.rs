@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::lobby::Lobby;
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::timer::{SystemClock, TurnTimer};
+
+/// How often the server checks for players who ran out of time.
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Clients sending longer lines than this are cut off, so that none can run the server out of
+/// memory. Messages are far shorter.
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// How long to wait before accepting again after failing to, e.g. when out of file descriptors.
+pub(crate) const ACCEPT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) type ConnectionId = usize;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct ServerConfig {
+    pub seed: u64,
+    pub turn_timer: Option<TurnTimer>,
+    /// How long a seat is kept for a player who lost their connection mid-game.
+    pub reconnect_grace: Duration,
+    /// How far behind the game spectators who see every hand are kept.
+    pub spectator_delay: Duration,
+    /// Takes over the seats of players who timed out or didn't come back.
+    pub replacement_bot: &'static str,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            seed: 0,
+            turn_timer: None,
+            reconnect_grace: Duration::from_secs(60),
+            spectator_delay: Duration::from_secs(30),
+            replacement_bot: "heuristic",
+        }
+    }
+}
+
+/// What connection threads tell the hub, which owns all game state.
+enum HubMessage {
+    Connected {
+        connection_id: ConnectionId,
+        outbox: Sender<ServerMessage>,
+    },
+    Received {
+        connection_id: ConnectionId,
+        message: Result<ClientMessage, String>,
+    },
+    Disconnected {
+        connection_id: ConnectionId,
+    },
+}
+
+/// Where messages to each connection go. A connection's writer thread sends them on.
+#[derive(Default)]
+pub(crate) struct Outboxes {
+    pub(crate) by_connection: HashMap<ConnectionId, Sender<ServerMessage>>,
+}
+
+impl Outboxes {
+    pub(crate) fn send(&self, connection_id: ConnectionId, message: ServerMessage) {
+        if let Some(outbox) = self.by_connection.get(&connection_id) {
+            // The connection may have just gone away, in which case the hub hears about it soon.
+            let _ = outbox.send(message);
+        }
+    }
+
+    pub(crate) fn send_error(&self, connection_id: ConnectionId, message: &str) {
+        self.send(
+            connection_id,
+            ServerMessage::Error {
+                message: message.to_owned(),
+            },
+        );
+    }
+}
+
+struct Hub {
+    outboxes: Outboxes,
+    lobby: Lobby,
+}
+
+impl Hub {
+    fn run(mut self, inbox: Receiver<HubMessage>) {
+        loop {
+            match inbox.recv_timeout(TICK_INTERVAL) {
+                Ok(message) => self.handle(message),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            self.lobby.tick(&self.outboxes);
+        }
+    }
+
+    fn handle(&mut self, message: HubMessage) {
+        match message {
+            HubMessage::Connected {
+                connection_id,
+                outbox,
+            } => {
+                self.outboxes.by_connection.insert(connection_id, outbox);
+            }
+            HubMessage::Received {
+                connection_id,
+                message,
+            } => match message {
+                Ok(message) => self.lobby.handle(&self.outboxes, connection_id, message),
+                Err(err) => self.outboxes.send_error(connection_id, &err),
+            },
+            HubMessage::Disconnected { connection_id } => {
+                self.lobby.disconnect(&self.outboxes, connection_id);
+                self.outboxes.by_connection.remove(&connection_id);
+            }
+        }
+    }
+}
+
+/// Lets connections of any kind talk to the hub thread, which owns the lobby.
+#[derive(Clone)]
+pub struct HubHandle {
+    sender: Sender<HubMessage>,
+    next_connection_id: Arc<AtomicUsize>,
+}
+
+impl HubHandle {
+    pub fn spawn(config: ServerConfig) -> HubHandle {
+        let (sender, receiver) = mpsc::channel();
+
+        // Bots aren't `Send`, so the lobby has to be created on the thread that owns it.
+        thread::spawn(move || {
+            let hub = Hub {
+                outboxes: Outboxes::default(),
+                lobby: Lobby::new(config, Arc::new(SystemClock)),
+            };
+            hub.run(receiver)
+        });
+
+        HubHandle {
+            sender,
+            next_connection_id: Arc::default(),
+        }
+    }
+
+    /// Registers a new connection, whose messages from the hub go to `outbox`.
+    pub(crate) fn connect(&self, outbox: Sender<ServerMessage>) -> ConnectionId {
+        let connection_id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        let _ = self.sender.send(HubMessage::Connected {
+            connection_id,
+            outbox,
+        });
+        connection_id
+    }
+
+    /// Hands a message, or why it couldn't be parsed, to the hub. Returns false once the hub is
+    /// gone.
+    pub(crate) fn receive(
+        &self,
+        connection_id: ConnectionId,
+        message: Result<ClientMessage, String>,
+    ) -> bool {
+        self.sender
+            .send(HubMessage::Received {
+                connection_id,
+                message,
+            })
+            .is_ok()
+    }
+
+    pub(crate) fn disconnect(&self, connection_id: ConnectionId) {
+        let _ = self.sender.send(HubMessage::Disconnected { connection_id });
+    }
+}
+
+/// Feeds lines from the client to the hub, and messages from the hub back to the client, one JSON
+/// object per line.
+fn spawn_connection(stream: TcpStream, hub: HubHandle) -> io::Result<()> {
+    let (outbox, inbox) = mpsc::channel::<ServerMessage>();
+    stream.set_nodelay(true)?;
+    let mut writer = stream.try_clone()?;
+
+    thread::spawn(move || {
+        for message in inbox {
+            let line = serde_json::to_string(&message).unwrap();
+            if writeln!(writer, "{}", line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let connection_id = hub.connect(outbox);
+
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+
+        while let Some(line) = read_line(&mut reader) {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message = serde_json::from_str(&line).map_err(|err| err.to_string());
+            if !hub.receive(connection_id, message) {
+                return;
+            }
+        }
+
+        hub.disconnect(connection_id);
+    });
+
+    Ok(())
+}
+
+/// The next line from the client, or `None` once it has hung up, sent something that isn't text or
+/// gone over `MAX_LINE_LEN`.
+fn read_line(reader: &mut impl BufRead) -> Option<String> {
+    let mut line = vec![];
+    let len = reader
+        .by_ref()
+        .take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .ok()?;
+
+    if len == 0 || (len > MAX_LINE_LEN && line.last() != Some(&b'\n')) {
+        return None;
+    }
+    String::from_utf8(line).ok()
+}
+
+/// Lets clients connecting over TCP into a lobby.
+pub struct Server {
+    listener: TcpListener,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections for good. Failing to accept one is only logged.
+    pub fn run(self, hub: HubHandle) {
+        for stream in self.listener.incoming() {
+            if let Err(err) = stream.and_then(|stream| spawn_connection(stream, hub.clone())) {
+                eprintln!("server: {}", err);
+                thread::sleep(ACCEPT_RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::Action;
+    use crate::protocol::TableView;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(addr: SocketAddr) -> Client {
+            let writer = TcpStream::connect(addr).unwrap();
+            Client {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+            }
+        }
+
+        fn send(&mut self, message: &ClientMessage) {
+            writeln!(self.writer, "{}", serde_json::to_string(message).unwrap()).unwrap();
+        }
+
+        fn receive(&mut self) -> ServerMessage {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+
+        /// Skips events until the next state update.
+        fn receive_state(&mut self) -> (TableView, Vec<Action>) {
+            loop {
+                if let ServerMessage::State {
+                    view,
+                    legal_actions,
+                } = self.receive()
+                {
+                    return (view, legal_actions);
+                }
+            }
+        }
+    }
+
+    fn start_server() -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let hub = HubHandle::spawn(ServerConfig {
+            seed: 3,
+            ..ServerConfig::default()
+        });
+
+        thread::spawn(move || server.run(hub));
+        addr
+    }
+
+    /// Seats two clients at a new room and readies them up, which starts the game.
+    fn start_game(addr: SocketAddr) -> [Client; 2] {
+        let mut first = Client::connect(addr);
+        first.send(&ClientMessage::CreateRoom {
+            ruleset: "standard".to_owned(),
+            seats: 2,
+        });
+        assert!(matches!(
+            first.receive(),
+            ServerMessage::Joined {
+                room_id: 1,
+                seat: 0,
+                ..
+            }
+        ));
+
+        let mut second = Client::connect(addr);
+        second.send(&ClientMessage::JoinRoom {
+            room_id: 1,
+            seat: None,
+        });
+        assert!(matches!(
+            second.receive(),
+            ServerMessage::Joined {
+                room_id: 1,
+                seat: 1,
+                ..
+            }
+        ));
+
+        let mut clients = [first, second];
+        for client in &mut clients {
+            client.send(&ClientMessage::Ready { ready: true });
+        }
+        clients
+    }
+
+    #[test]
+    fn clients_play_a_game_to_the_end() {
+        let mut clients = start_game(start_server());
+
+        let mut views: Vec<(TableView, Vec<Action>)> = clients
+            .iter_mut()
+            .map(|client| client.receive_state())
+            .collect();
+
+        assert_eq!(views[0].0.hand.len(), 7);
+        assert_eq!(views[0].0.hand_sizes, vec![7, 7]);
+        assert!(!views[0].1.is_empty());
+        assert!(views[1].1.is_empty());
+
+        let winner = loop {
+            let current = views[0].0.current_player_idx;
+            // The last legal action plays a card whenever possible, so the game can't stall.
+            let action = *views[current].1.last().unwrap();
+            clients[current].send(&ClientMessage::Action { action });
+
+            let mut game_over = None;
+            for (seat, client) in clients.iter_mut().enumerate() {
+                loop {
+                    match client.receive() {
+                        ServerMessage::State {
+                            view,
+                            legal_actions,
+                        } => views[seat] = (view, legal_actions),
+                        ServerMessage::Event { .. } | ServerMessage::Room { .. } => continue,
+                        ServerMessage::GameOver { winner, hands, .. } => {
+                            assert!(hands[winner].is_empty());
+                            game_over = Some(winner);
+                        }
+                        message => panic!("unexpected message {:?}", message),
+                    }
+
+                    if views[seat].0.winner.is_none() || game_over.is_some() {
+                        break;
+                    }
+                }
+            }
+
+            if let Some(winner) = game_over {
+                break winner;
+            }
+        };
+
+        assert_eq!(views[1].0.winner, Some(winner));
+
+        clients[0].send(&ClientMessage::Scores);
+        let scores = loop {
+            if let ServerMessage::Scores { scores } = clients[0].receive() {
+                break scores;
+            }
+        };
+
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[winner].wins, 1);
+        assert_eq!(scores[1 - winner].wins, 0);
+    }
+
+    #[test]
+    fn server_rejects_actions_out_of_turn() {
+        let [mut first, mut second] = start_game(start_server());
+
+        let (_, legal_actions) = first.receive_state();
+        second.receive_state();
+
+        second.send(&ClientMessage::Action {
+            action: legal_actions[0],
+        });
+        assert_eq!(
+            second.receive(),
+            ServerMessage::Error {
+                message: "not your turn".to_owned()
+            }
+        );
+
+        first.send(&ClientMessage::Action {
+            action: Action::CallBluff,
+        });
+        assert_eq!(
+            first.receive(),
+            ServerMessage::Error {
+                message: "illegal action CallBluff".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn server_rejects_unknown_rooms_and_malformed_lines() {
+        let mut second = Client::connect(start_server());
+
+        second.send(&ClientMessage::JoinRoom {
+            room_id: 7,
+            seat: None,
+        });
+        assert_eq!(
+            second.receive(),
+            ServerMessage::Error {
+                message: "no such room".to_owned()
+            }
+        );
+
+        writeln!(second.writer, "not json").unwrap();
+        assert!(matches!(second.receive(), ServerMessage::Error { .. }));
+    }
+
+    #[test]
+    fn overlong_lines_cut_the_client_off() {
+        let long_line = "x".repeat(MAX_LINE_LEN + 1);
+        let input = format!("{{}}\n{}\n{{}}\n", long_line);
+        let mut reader = io::Cursor::new(input.as_bytes());
+
+        assert_eq!(read_line(&mut reader), Some("{}\n".to_owned()));
+        assert_eq!(read_line(&mut reader), None);
+
+        let mut reader = io::Cursor::new(&b"last line without a newline"[..]);
+        assert!(read_line(&mut reader).is_some());
+        assert_eq!(read_line(&mut reader), None);
+    }
+}
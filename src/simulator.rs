@@ -0,0 +1,277 @@
+use std::fmt;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::agent::{self, Agent, GameError};
+use crate::bots;
+use crate::game_executor::{Event, GameRuntime};
+use crate::ruleset::Ruleset;
+
+/// z-score of a two-sided 95% confidence interval.
+const CONFIDENCE_Z: f64 = 1.96;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SimulationConfig {
+    /// One bot name per seat.
+    pub bots: Vec<String>,
+    pub ruleset: Ruleset,
+    /// Every seed plays exactly one game, so the same range always gives the same results.
+    pub seeds: Range<u64>,
+    pub threads: usize,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SimulationReport {
+    pub bots: Vec<String>,
+    pub wins: Vec<usize>,
+    pub finished_games: usize,
+    pub total_actions: usize,
+    pub total_reshuffles: usize,
+    /// Seeds of the games that panicked.
+    pub panics: Vec<u64>,
+    /// Seeds of the games that stopped on an illegal action or an agent error.
+    pub errors: Vec<(u64, String)>,
+}
+
+enum GameOutcome {
+    Finished {
+        winner: usize,
+        actions: usize,
+        reshuffles: usize,
+    },
+    Failed(GameError),
+    Panicked,
+}
+
+impl SimulationReport {
+    fn new(bots: &[String]) -> SimulationReport {
+        SimulationReport {
+            bots: bots.to_vec(),
+            wins: vec![0; bots.len()],
+            finished_games: 0,
+            total_actions: 0,
+            total_reshuffles: 0,
+            panics: vec![],
+            errors: vec![],
+        }
+    }
+
+    fn record(&mut self, seed: u64, outcome: GameOutcome) {
+        match outcome {
+            GameOutcome::Finished {
+                winner,
+                actions,
+                reshuffles,
+            } => {
+                self.wins[winner] += 1;
+                self.finished_games += 1;
+                self.total_actions += actions;
+                self.total_reshuffles += reshuffles;
+            }
+            GameOutcome::Failed(err) => self.errors.push((seed, err.to_string())),
+            GameOutcome::Panicked => self.panics.push(seed),
+        }
+    }
+
+    fn merge(&mut self, other: SimulationReport) {
+        for (wins, other_wins) in self.wins.iter_mut().zip(other.wins) {
+            *wins += other_wins;
+        }
+
+        self.finished_games += other.finished_games;
+        self.total_actions += other.total_actions;
+        self.total_reshuffles += other.total_reshuffles;
+        self.panics.extend(other.panics);
+        self.errors.extend(other.errors);
+        self.panics.sort_unstable();
+        self.errors.sort();
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let games = self.finished_games;
+
+        writeln!(
+            f,
+            "games: {} finished, {} panicked, {} failed",
+            games,
+            self.panics.len(),
+            self.errors.len()
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<6}{:<12}{:>8}{:>10}   95% CI",
+            "seat", "bot", "wins", "win rate"
+        )?;
+
+        for (seat, (bot, &wins)) in self.bots.iter().zip(&self.wins).enumerate() {
+            let (low, high) = wilson_interval(wins, games);
+            writeln!(
+                f,
+                "{:<6}{:<12}{:>8}{:>9.1}%   {:.1}%..{:.1}%",
+                seat + 1,
+                bot,
+                wins,
+                100.0 * wins as f64 / games.max(1) as f64,
+                100.0 * low,
+                100.0 * high
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "average game length: {:.1} actions",
+            self.total_actions as f64 / games.max(1) as f64
+        )?;
+        writeln!(
+            f,
+            "draw pile reshuffles: {:.2} per game, {} in total",
+            self.total_reshuffles as f64 / games.max(1) as f64,
+            self.total_reshuffles
+        )?;
+
+        for seed in &self.panics {
+            writeln!(f, "seed {}: panicked", seed)?;
+        }
+
+        for (seed, err) in &self.errors {
+            writeln!(f, "seed {}: {}", seed, err)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The Wilson score interval of a binomial proportion, which unlike the normal approximation
+/// stays within `0..=1` for win rates close to either end.
+pub(crate) fn wilson_interval(successes: usize, trials: usize) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = CONFIDENCE_Z * CONFIDENCE_Z;
+
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = CONFIDENCE_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+fn play_seed(config: &SimulationConfig, seed: u64) -> GameOutcome {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut agents: Vec<Box<dyn Agent>> = config
+            .bots
+            .iter()
+            .map(|name| bots::bot_by_name(name, rng.gen()).unwrap())
+            .collect();
+
+        let mut game_runtime = GameRuntime::new(
+            agents.len(),
+            config.ruleset,
+            StdRng::seed_from_u64(rng.gen()),
+        );
+
+        agent::play_game(&mut game_runtime, &mut agents).map(|winner| (winner, game_runtime))
+    }));
+
+    match result {
+        Ok(Ok((winner, game_runtime))) => GameOutcome::Finished {
+            winner,
+            actions: game_runtime.actions_executed(),
+            reshuffles: game_runtime
+                .events()
+                .iter()
+                .filter(|&&event| event == Event::ReshuffledDiscardPile)
+                .count(),
+        },
+        Ok(Err(err)) => GameOutcome::Failed(err),
+        Err(_) => GameOutcome::Panicked,
+    }
+}
+
+/// Plays one game per seed, spreading the games over `config.threads` threads.
+pub fn simulate(config: &SimulationConfig) -> SimulationReport {
+    let next_seed = AtomicU64::new(config.seeds.start);
+    let mut report = SimulationReport::new(&config.bots);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..config.threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut report = SimulationReport::new(&config.bots);
+
+                    loop {
+                        let seed = next_seed.fetch_add(1, Ordering::Relaxed);
+                        if seed >= config.seeds.end {
+                            return report;
+                        }
+
+                        report.record(seed, play_seed(config, seed));
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            report.merge(worker.join().unwrap());
+        }
+    });
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wilson_interval_contains_the_observed_rate() {
+        let (low, high) = wilson_interval(50, 100);
+        assert!((low - 0.4038).abs() < 1e-3);
+        assert!((high - 0.5962).abs() < 1e-3);
+
+        assert_eq!(wilson_interval(0, 10).0, 0.0);
+        assert_eq!(wilson_interval(10, 10).1, 1.0);
+        assert_eq!(wilson_interval(0, 0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn simulation_results_do_not_depend_on_thread_count() {
+        let mut config = SimulationConfig {
+            bots: vec![
+                "heuristic".to_owned(),
+                "random".to_owned(),
+                "greedy".to_owned(),
+            ],
+            ruleset: Ruleset::default(),
+            seeds: 10..40,
+            threads: 1,
+        };
+
+        let report = simulate(&config);
+
+        assert_eq!(report.finished_games, 30);
+        assert_eq!(report.wins.iter().sum::<usize>(), 30);
+        assert!(report.total_actions > 0);
+        assert!(report.panics.is_empty());
+        assert!(report.errors.is_empty());
+
+        config.threads = 4;
+        assert_eq!(simulate(&config), report);
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+use crate::game_state::Action;
+use crate::view::PlayerView;
+
+/// Where the server gets the time from, so that tests can move it forward by hand.
+pub(crate) trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[cfg(test)]
+#[derive(Clone)]
+pub(crate) struct ManualClock {
+    started_at: Instant,
+    elapsed: std::sync::Arc<std::sync::Mutex<Duration>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub(crate) fn new() -> ManualClock {
+        ManualClock {
+            started_at: Instant::now(),
+            elapsed: Default::default(),
+        }
+    }
+
+    pub(crate) fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.started_at + *self.elapsed.lock().unwrap()
+    }
+}
+
+/// What gets played for a player who ran out of time.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TimeoutAction {
+    /// Takes whatever draw is on offer, then passes.
+    DrawAndPass,
+    /// Plays the first card or color on offer, and only draws or passes when there's none.
+    FirstLegalAction,
+}
+
+impl TimeoutAction {
+    pub fn by_name(name: &str) -> Option<TimeoutAction> {
+        match name {
+            "draw-and-pass" => Some(TimeoutAction::DrawAndPass),
+            "first-legal" => Some(TimeoutAction::FirstLegalAction),
+            _ => None,
+        }
+    }
+
+    /// The next action to take for the player who timed out, out of `legal_actions`, which is
+    /// never empty. Played repeatedly, this ends their turn. It only goes by what is on offer, so
+    /// a player who never answers gets nothing they couldn't see for themselves.
+    pub(crate) fn choose(self, _view: &PlayerView, legal_actions: &[Action]) -> Action {
+        match self {
+            TimeoutAction::DrawAndPass => legal_actions
+                .iter()
+                .copied()
+                .find(|action| matches!(action, Action::DrawCard { .. } | Action::Pass))
+                .unwrap_or(legal_actions[0]),
+            TimeoutAction::FirstLegalAction => legal_actions
+                .iter()
+                .copied()
+                .find(|action| matches!(action, Action::Play { .. } | Action::ChooseColor { .. }))
+                .unwrap_or(legal_actions[0]),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TurnTimer {
+    pub turn_time: Duration,
+    pub on_timeout: TimeoutAction,
+    /// Hands a seat over to a bot after this many timeouts in a row.
+    pub replace_after: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game_executor::GameRuntime;
+    use crate::game_state::DrawAmount;
+    use crate::ruleset::Ruleset;
+
+    fn choose(on_timeout: TimeoutAction, game_runtime: &GameRuntime) -> Action {
+        let view = PlayerView::new(game_runtime, game_runtime.current_player_idx());
+        on_timeout.choose(&view, &game_runtime.legal_actions())
+    }
+
+    #[test]
+    fn draw_and_pass_ends_the_turn_without_playing() {
+        let mut game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(1));
+        let hand_size = game_runtime.hand(0).len();
+
+        assert_eq!(
+            choose(TimeoutAction::DrawAndPass, &game_runtime),
+            Action::DrawCard {
+                amount: DrawAmount::One
+            }
+        );
+
+        game_runtime
+            .execute_action(choose(TimeoutAction::DrawAndPass, &game_runtime))
+            .unwrap();
+        assert_eq!(
+            choose(TimeoutAction::DrawAndPass, &game_runtime),
+            Action::Pass
+        );
+
+        game_runtime
+            .execute_action(choose(TimeoutAction::DrawAndPass, &game_runtime))
+            .unwrap();
+        assert_eq!(game_runtime.current_player_idx(), 1);
+        assert_eq!(game_runtime.hand(0).len(), hand_size + 1);
+    }
+
+    #[test]
+    fn first_legal_action_plays_a_card_when_it_can() {
+        let game_runtime = (0..)
+            .map(|seed| GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(seed)))
+            .find(|game_runtime| game_runtime.has_any_playable())
+            .unwrap();
+        let first_play = game_runtime
+            .legal_actions()
+            .into_iter()
+            .find(|action| matches!(action, Action::Play { .. }));
+
+        assert_eq!(
+            Some(choose(TimeoutAction::FirstLegalAction, &game_runtime)),
+            first_play
+        );
+    }
+
+    #[test]
+    fn manual_clock_only_moves_when_advanced() {
+        let clock = ManualClock::new();
+        let started_at = clock.now();
+
+        assert_eq!(clock.now(), started_at);
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(clock.now() - started_at, Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::agent::{self, Agent};
+use crate::bots;
+use crate::game_executor::GameRuntime;
+use crate::ruleset::Ruleset;
+use crate::view::OmniscientView;
+
+const INITIAL_RATING: f64 = 1500.0;
+
+/// How far a single game can move a rating. Multiplayer games split it across every opponent.
+const K_FACTOR: f64 = 32.0;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Format {
+    /// Every combination of bots meets at a table once per seat rotation.
+    RoundRobin,
+    /// Every round seats bots of similar rating that have yet to meet, `rounds` times over.
+    Swiss { rounds: usize },
+}
+
+/// Bot names in seat order.
+pub type Table = Vec<String>;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Rating {
+    pub rating: f64,
+    pub games: usize,
+}
+
+/// Elo ratings keyed by bot name, extended to multiplayer tables by scoring every game as a
+/// match between each pair of players at the table.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Ratings {
+    by_name: BTreeMap<String, Rating>,
+}
+
+impl Ratings {
+    /// Reads ratings saved by `save`. A missing file means nobody has played yet.
+    pub fn load(path: &Path) -> io::Result<Ratings> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Ratings::default()),
+            Err(err) => return Err(err),
+        };
+
+        let mut ratings = Ratings::default();
+
+        for line in contents.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid ratings line: {:?}", line),
+                )
+            };
+
+            // The name goes last since engine command lines can have spaces in them.
+            let mut fields = line.splitn(3, ' ');
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(rating), Some(games), Some(name)) if !name.is_empty() => {
+                    ratings.by_name.insert(
+                        name.to_owned(),
+                        Rating {
+                            rating: rating.parse().map_err(|_| invalid_line())?,
+                            games: games.parse().map_err(|_| invalid_line())?,
+                        },
+                    );
+                }
+                _ => return Err(invalid_line()),
+            }
+        }
+
+        Ok(ratings)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::from("# rating games name\n");
+
+        for (name, rating) in &self.by_name {
+            contents += &format!("{} {} {}\n", rating.rating, rating.games, name);
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn get(&self, name: &str) -> Rating {
+        self.by_name.get(name).copied().unwrap_or(Rating {
+            rating: INITIAL_RATING,
+            games: 0,
+        })
+    }
+
+    /// Best rated first.
+    pub fn standings(&self) -> Vec<(&str, Rating)> {
+        let mut standings: Vec<(&str, Rating)> = self
+            .by_name
+            .iter()
+            .map(|(name, &rating)| (name.as_str(), rating))
+            .collect();
+
+        standings.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+        standings
+    }
+
+    /// Updates the ratings of everybody at `table`, where `ranks[seat]` is how many players
+    /// finished strictly ahead of that seat.
+    pub(crate) fn record_game(&mut self, table: &[String], ranks: &[usize]) {
+        let old_ratings: Vec<f64> = table.iter().map(|name| self.get(name).rating).collect();
+        let num_opponents = (table.len() - 1) as f64;
+
+        for (seat, name) in table.iter().enumerate() {
+            let mut score_difference = 0.0;
+
+            for opponent in (0..table.len()).filter(|&opponent| opponent != seat) {
+                let score = match ranks[seat].cmp(&ranks[opponent]) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+
+                let expected_score =
+                    1.0 / (1.0 + 10f64.powf((old_ratings[opponent] - old_ratings[seat]) / 400.0));
+
+                score_difference += score - expected_score;
+            }
+
+            let mut rating = self.get(name);
+            rating.rating += K_FACTOR / num_opponents * score_difference;
+            rating.games += 1;
+            self.by_name.insert(name.clone(), rating);
+        }
+    }
+}
+
+/// The winner finishes first, and everybody else is ranked by how many cards they were left
+/// holding.
+pub(crate) fn finish_ranks(view: &OmniscientView) -> Vec<usize> {
+    let hand_sizes = &view.table.hand_sizes;
+
+    hand_sizes
+        .iter()
+        .map(|&hand_size| {
+            hand_sizes
+                .iter()
+                .filter(|&&other| other < hand_size)
+                .count()
+        })
+        .collect()
+}
+
+fn combinations(bots: &[String], table_size: usize) -> Vec<Vec<String>> {
+    if table_size == 0 {
+        return vec![vec![]];
+    }
+
+    if bots.len() < table_size {
+        return vec![];
+    }
+
+    let mut with_first: Vec<Vec<String>> = combinations(&bots[1..], table_size - 1)
+        .into_iter()
+        .map(|mut rest| {
+            rest.insert(0, bots[0].clone());
+            rest
+        })
+        .collect();
+
+    with_first.extend(combinations(&bots[1..], table_size));
+    with_first
+}
+
+/// Every group of `table_size` bots, once in each rotation of the seat order so that nobody
+/// keeps the advantage of playing first.
+pub(crate) fn round_robin_tables(bots: &[String], table_size: usize) -> Vec<Table> {
+    combinations(bots, table_size)
+        .into_iter()
+        .flat_map(|table| {
+            (0..table.len()).map(move |rotation| {
+                let mut table = table.clone();
+                table.rotate_left(rotation);
+                table
+            })
+        })
+        .collect()
+}
+
+/// Who has met whom and who has sat out so far in a Swiss tournament.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub(crate) struct SwissHistory {
+    /// How many times each pair of bots shared a table, keyed with the smaller name first.
+    meetings: BTreeMap<(String, String), usize>,
+    byes: BTreeMap<String, usize>,
+}
+
+impl SwissHistory {
+    fn pair(a: &str, b: &str) -> (String, String) {
+        if a <= b {
+            (a.to_owned(), b.to_owned())
+        } else {
+            (b.to_owned(), a.to_owned())
+        }
+    }
+
+    pub(crate) fn meetings(&self, a: &str, b: &str) -> usize {
+        self.meetings.get(&Self::pair(a, b)).copied().unwrap_or(0)
+    }
+
+    pub(crate) fn byes(&self, name: &str) -> usize {
+        self.byes.get(name).copied().unwrap_or(0)
+    }
+
+    /// Records a round of `tables` drawn from `bots`, where anybody not seated had a bye.
+    pub(crate) fn record_round(&mut self, bots: &[String], tables: &[Table]) {
+        for table in tables {
+            for (seat, a) in table.iter().enumerate() {
+                for b in &table[seat + 1..] {
+                    *self.meetings.entry(Self::pair(a, b)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        for name in bots {
+            if !tables.iter().any(|table| table.contains(name)) {
+                *self.byes.entry(name.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+/// Seats bots of similar rating together, rotating the seat order every round. The best rated
+/// bot left opens each table, which is then filled with whoever left it has met the players
+/// already seated the fewest times, the best rated among them on a tie. A single bot left over
+/// sits the round out, which goes to the lowest rated of those who have sat out the least.
+pub(crate) fn swiss_tables(
+    bots: &[String],
+    table_size: usize,
+    ratings: &Ratings,
+    history: &SwissHistory,
+    round: usize,
+) -> Vec<Table> {
+    let mut by_rating = bots.to_vec();
+    by_rating.sort_by(|a, b| ratings.get(b).rating.total_cmp(&ratings.get(a).rating));
+
+    if by_rating.len() % table_size == 1 {
+        let (bye_idx, _) = by_rating
+            .iter()
+            .enumerate()
+            .rev()
+            .min_by_key(|(_, name)| history.byes(name))
+            .unwrap();
+        by_rating.remove(bye_idx);
+    }
+
+    let mut tables = vec![];
+
+    while !by_rating.is_empty() {
+        let mut table = vec![by_rating.remove(0)];
+
+        while table.len() < table_size && !by_rating.is_empty() {
+            let (next_idx, _) = by_rating
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, name)| {
+                    table
+                        .iter()
+                        .map(|seated| history.meetings(seated, name))
+                        .sum::<usize>()
+                })
+                .unwrap();
+            table.push(by_rating.remove(next_idx));
+        }
+
+        let rotation = round % table.len();
+        table.rotate_left(rotation);
+        tables.push(table);
+    }
+
+    tables
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct TournamentConfig {
+    pub bots: Vec<String>,
+    pub table_size: usize,
+    pub format: Format,
+    pub ruleset: Ruleset,
+    /// Game `i` of the tournament is played with seed `first_seed + i`.
+    pub first_seed: u64,
+    pub threads: usize,
+}
+
+/// Plays one table, returning the finish ranks or why the game couldn't be finished.
+fn play_table(table: &[String], ruleset: Ruleset, seed: u64) -> Result<Vec<usize>, String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut agents: Vec<Box<dyn Agent>> = table
+            .iter()
+            .map(|name| bots::bot_by_name(name, rng.gen()).unwrap())
+            .collect();
+
+        let mut game_runtime =
+            GameRuntime::new(agents.len(), ruleset, StdRng::seed_from_u64(rng.gen()));
+
+        agent::play_game(&mut game_runtime, &mut agents)
+            .map(|_| finish_ranks(&OmniscientView::new(&game_runtime)))
+            .map_err(|err| err.to_string())
+    }));
+
+    result.unwrap_or_else(|_| Err("panicked".to_owned()))
+}
+
+/// Plays every table of a round in parallel. Results come back in table order, so ratings don't
+/// depend on which game happened to finish first.
+fn play_round(
+    tables: &[Table],
+    config: &TournamentConfig,
+    first_seed: u64,
+) -> Vec<Result<Vec<usize>, String>> {
+    let next_table = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<Vec<usize>, String>>> = vec![None; tables.len()];
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..config.threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+
+                    loop {
+                        let table_idx = next_table.fetch_add(1, Ordering::Relaxed);
+                        if table_idx >= tables.len() {
+                            return results;
+                        }
+
+                        let seed = first_seed + table_idx as u64;
+                        results.push((
+                            table_idx,
+                            play_table(&tables[table_idx], config.ruleset, seed),
+                        ));
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            for (table_idx, result) in worker.join().unwrap() {
+                results[table_idx] = Some(result);
+            }
+        }
+    });
+
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// Runs the whole tournament on top of `ratings`, returning the games that couldn't be finished
+/// along with their seeds.
+pub fn run_tournament(
+    config: &TournamentConfig,
+    ratings: &mut Ratings,
+) -> Vec<(u64, Table, String)> {
+    let num_rounds = match config.format {
+        Format::RoundRobin => 1,
+        Format::Swiss { rounds } => rounds,
+    };
+
+    let mut next_seed = config.first_seed;
+    let mut failures = vec![];
+    let mut history = SwissHistory::default();
+
+    for round in 0..num_rounds {
+        let tables = match config.format {
+            Format::RoundRobin => round_robin_tables(&config.bots, config.table_size),
+            Format::Swiss { .. } => {
+                swiss_tables(&config.bots, config.table_size, ratings, &history, round)
+            }
+        };
+        history.record_round(&config.bots, &tables);
+
+        for (table_idx, (table, result)) in tables
+            .iter()
+            .zip(play_round(&tables, config, next_seed))
+            .enumerate()
+        {
+            match result {
+                Ok(ranks) => ratings.record_game(table, &ranks),
+                Err(err) => failures.push((next_seed + table_idx as u64, table.clone(), err)),
+            }
+        }
+
+        next_seed += tables.len() as u64;
+    }
+
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn winning_a_game_moves_rating_from_the_losers() {
+        let mut ratings = Ratings::default();
+        let table = names(&["a", "b", "c"]);
+
+        ratings.record_game(&table, &[1, 0, 2]);
+
+        assert!(ratings.get("b").rating > INITIAL_RATING);
+        assert_eq!(ratings.get("a").rating, INITIAL_RATING);
+        assert!(ratings.get("c").rating < INITIAL_RATING);
+        assert_eq!(ratings.get("a").games, 1);
+
+        let total: f64 = table.iter().map(|name| ratings.get(name).rating).sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn round_robin_rotates_every_table() {
+        let tables = round_robin_tables(&names(&["a", "b", "c", "d"]), 3);
+
+        // Four groups of three, each played from three seat orders.
+        assert_eq!(tables.len(), 12);
+        assert!(tables.contains(&names(&["a", "b", "c"])));
+        assert!(tables.contains(&names(&["b", "c", "a"])));
+        assert!(tables.contains(&names(&["c", "a", "b"])));
+
+        for name in ["a", "b", "c", "d"] {
+            let first_seats = tables.iter().filter(|table| table[0] == name).count();
+            assert_eq!(first_seats, 3);
+        }
+    }
+
+    #[test]
+    fn swiss_seats_similar_ratings_together() {
+        let mut ratings = Ratings::default();
+        for (name, rating) in [("a", 1400.0), ("b", 1700.0), ("c", 1450.0), ("d", 1650.0)] {
+            ratings
+                .by_name
+                .insert(name.to_owned(), Rating { rating, games: 1 });
+        }
+
+        let bots = names(&["a", "b", "c", "d", "e"]);
+        let mut history = SwissHistory::default();
+
+        let first_round = swiss_tables(&bots, 2, &ratings, &history, 0);
+        assert_eq!(first_round, vec![names(&["b", "d"]), names(&["e", "c"])]);
+        history.record_round(&bots, &first_round);
+
+        // Seats rotate between rounds, nobody meets the same opponent again, and the bye moves on
+        // to the lowest rated bot that hasn't had one.
+        assert_eq!(
+            swiss_tables(&bots, 2, &ratings, &history, 1),
+            vec![names(&["e", "b"]), names(&["a", "d"])]
+        );
+    }
+
+    #[test]
+    fn swiss_rotates_byes_and_avoids_rematches() {
+        let bots = names(&["a", "b", "c"]);
+        let ratings = Ratings::default();
+        let mut history = SwissHistory::default();
+
+        for round in 0..3 {
+            let tables = swiss_tables(&bots, 2, &ratings, &history, round);
+            assert_eq!(tables.len(), 1);
+            history.record_round(&bots, &tables);
+        }
+
+        for (idx, a) in bots.iter().enumerate() {
+            assert_eq!(history.byes(a), 1);
+            for b in &bots[idx + 1..] {
+                assert_eq!(history.meetings(a, b), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn ratings_survive_a_save_and_load() {
+        let path = std::env::temp_dir().join(format!("uno-ratings-{}.txt", std::process::id()));
+        let mut ratings = Ratings::default();
+        ratings.record_game(&names(&["greedy", "random"]), &[0, 1]);
+        ratings.record_game(&names(&["engine:./my-engine --fast", "greedy"]), &[1, 0]);
+
+        ratings.save(&path).unwrap();
+        let loaded = Ratings::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, ratings);
+        assert_eq!(loaded.get("greedy").games, 2);
+        assert_eq!(loaded.get("engine:./my-engine --fast").games, 1);
+        assert_eq!(
+            Ratings::load(&path).unwrap(),
+            Ratings::default(),
+            "a missing file starts from scratch"
+        );
+    }
+
+    #[test]
+    fn tournament_ranks_the_stronger_bot_first() {
+        let config = TournamentConfig {
+            bots: names(&["random", "heuristic"]),
+            table_size: 2,
+            format: Format::Swiss { rounds: 20 },
+            ruleset: Ruleset::default(),
+            first_seed: 0,
+            threads: 2,
+        };
+        let mut ratings = Ratings::default();
+
+        assert!(run_tournament(&config, &mut ratings).is_empty());
+        assert_eq!(ratings.standings()[0].0, "heuristic");
+        assert_eq!(ratings.get("random").games, 20);
+    }
+}
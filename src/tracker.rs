@@ -0,0 +1,389 @@
+use alloc::vec::Vec;
+
+use crate::game_executor::Event;
+use crate::game_state::{Card, CardColor, Deck, WildCard, WildCardKind};
+use crate::packed::{self, PackedCard, PackedHand, SetBits, NUM_CARD_TYPES};
+use crate::view::PlayerView;
+
+const ALL_CARD_TYPES: u64 = (1 << NUM_CARD_TYPES) - 1;
+
+/// Counts the cards one player has seen: their own hand, the discard pile and any hand that gets
+/// shown to them, and narrows down the other hands with what bluff calls give away. Every card
+/// they haven't seen is taken to be equally likely to be anywhere they can't see.
+///
+/// Give it every event with `observe_event`, and the player's view with `update` before asking
+/// it anything.
+#[derive(Clone, Debug)]
+pub struct CardTracker {
+    player_idx: Option<usize>,
+    /// Copies of each card type that the player hasn't seen, wherever they are.
+    unseen: [u8; NUM_CARD_TYPES],
+    seats: Vec<SeatKnowledge>,
+    top_card: Option<Card>,
+    active_color: Option<CardColor>,
+    /// Who played the last draw four, and the color it was played on.
+    last_draw_four: Option<(usize, Option<CardColor>)>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct SeatKnowledge {
+    hand_size: usize,
+    /// Cards known to be in the hand.
+    known: PackedHand,
+    /// Card types the rest of the hand can't be, from a draw four that wasn't a bluff. Drawing
+    /// brings in cards that could be anything again.
+    excluded: u64,
+    /// Card types the hand holds at least one of, from a draw four that was a bluff.
+    holds: u64,
+}
+
+impl Default for CardTracker {
+    fn default() -> CardTracker {
+        CardTracker::new()
+    }
+}
+
+impl CardTracker {
+    pub fn new() -> CardTracker {
+        CardTracker {
+            player_idx: None,
+            unseen: full_deck_counts(),
+            seats: Vec::new(),
+            top_card: None,
+            active_color: None,
+            last_draw_four: None,
+        }
+    }
+
+    fn seat_mut(&mut self, player_idx: usize) -> &mut SeatKnowledge {
+        if player_idx >= self.seats.len() {
+            self.seats
+                .resize_with(player_idx + 1, SeatKnowledge::default);
+        }
+        &mut self.seats[player_idx]
+    }
+
+    fn seat(&self, player_idx: usize) -> SeatKnowledge {
+        self.seats.get(player_idx).cloned().unwrap_or_default()
+    }
+
+    /// Recounts the unseen cards from what `view` shows. A reshuffled discard pile goes back to
+    /// being unseen.
+    pub fn update(&mut self, view: &PlayerView) {
+        self.player_idx = Some(view.player_idx);
+        self.top_card = Some(view.table.top_card());
+        self.active_color = view.table.active_color;
+
+        for (player_idx, &hand_size) in view.table.hand_sizes.iter().enumerate() {
+            self.seat_mut(player_idx).hand_size = hand_size;
+        }
+        self.seat_mut(view.player_idx).known = PackedHand::from_cards(&view.hand);
+
+        let mut unseen = full_deck_counts();
+        for &card in view.table.discard_pile {
+            let count = &mut unseen[PackedCard::new(card).index()];
+            *count = count.saturating_sub(1);
+        }
+        for (card, known) in self.seats.iter().flat_map(|seat| seat.known.iter()) {
+            let count = &mut unseen[card.index()];
+            *count = count.saturating_sub(known as u8);
+        }
+        self.unseen = unseen;
+    }
+
+    pub fn observe_event(&mut self, event: &Event) {
+        match *event {
+            Event::Played { player_idx, card } => {
+                let seat = self.seat_mut(player_idx);
+                seat.hand_size = seat.hand_size.saturating_sub(1);
+                let was_known = seat.known.remove(PackedCard::new(card));
+                if let Some(color) = card.get_color() {
+                    // It may have been the only card of its color.
+                    seat.holds &= !packed::color_mask(color);
+                }
+
+                if !was_known {
+                    let count = &mut self.unseen[PackedCard::new(card).index()];
+                    *count = count.saturating_sub(1);
+                }
+
+                if let Card::Special(WildCard {
+                    kind: WildCardKind::DrawFour,
+                }) = card
+                {
+                    self.last_draw_four = Some((player_idx, self.active_color));
+                }
+                self.top_card = Some(card);
+                self.active_color = card.get_color();
+            }
+            Event::ChoseColor { color, .. } => self.active_color = Some(color),
+            Event::Drew { player_idx, amount } => {
+                let seat = self.seat_mut(player_idx);
+                seat.hand_size += amount;
+                seat.excluded = 0;
+            }
+            Event::CalledBluff { succeeded, .. } => {
+                if let Some((bluffer_idx, Some(color))) = self.last_draw_four.take() {
+                    let seat = self.seat_mut(bluffer_idx);
+                    if succeeded {
+                        seat.holds |= packed::color_mask(color);
+                    } else {
+                        seat.excluded |= packed::color_mask(color);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Records that `player_idx`'s whole hand was shown, as some house rules do to whoever
+    /// challenges a draw four.
+    pub fn reveal(&mut self, player_idx: usize, hand: &[Card]) {
+        let seat = self.seat_mut(player_idx);
+        seat.hand_size = hand.len();
+        seat.known = PackedHand::from_cards(hand);
+        seat.excluded = 0;
+        seat.holds = 0;
+
+        for &card in hand {
+            let count = &mut self.unseen[PackedCard::new(card).index()];
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// How many copies of `card` the player hasn't seen.
+    pub fn unseen(&self, card: Card) -> usize {
+        self.unseen[PackedCard::new(card).index()] as usize
+    }
+
+    pub fn num_unseen(&self) -> usize {
+        self.unseen.iter().map(|&count| count as usize).sum()
+    }
+
+    fn count_unseen(&self, card_types: u64) -> usize {
+        SetBits(card_types)
+            .map(|card| self.unseen[card.index()] as usize)
+            .sum()
+    }
+
+    /// The share of the unseen cards each card type makes up, leaving out the types that have all
+    /// been seen.
+    pub fn unseen_distribution(&self) -> Vec<(Card, f64)> {
+        let num_unseen = self.num_unseen() as f64;
+
+        (0..NUM_CARD_TYPES)
+            .filter(|&idx| self.unseen[idx] > 0)
+            .filter_map(PackedCard::from_index)
+            .map(|card| (card.unpack(), self.unseen[card.index()] as f64 / num_unseen))
+            .collect()
+    }
+
+    /// How many copies of `card` are still to be played by somebody else or drawn, whether the
+    /// player has seen them in a hand or not.
+    pub fn remaining(&self, card: Card) -> usize {
+        let card = PackedCard::new(card);
+        let known_elsewhere: usize = self
+            .seats
+            .iter()
+            .enumerate()
+            .filter(|&(player_idx, _)| Some(player_idx) != self.player_idx)
+            .map(|(_, seat)| seat.known.count(card))
+            .sum();
+
+        self.unseen[card.index()] as usize + known_elsewhere
+    }
+
+    /// How many copies of `card` `player_idx` is expected to hold.
+    pub fn expected_in_hand(&self, player_idx: usize, card: Card) -> f64 {
+        let seat = self.seat(player_idx);
+        let card = PackedCard::new(card);
+        let known = seat.known.count(card) as f64;
+        if seat.excluded & card.bit() != 0 {
+            return known;
+        }
+
+        let pool = self.count_unseen(ALL_CARD_TYPES & !seat.excluded);
+        let unknown = seat.hand_size.saturating_sub(seat.known.len());
+        if pool == 0 {
+            return known;
+        }
+
+        known + unknown as f64 * self.unseen[card.index()] as f64 / pool as f64
+    }
+
+    /// The probability that `player_idx` holds at least one card of `color`.
+    pub fn probability_holds_color(&self, player_idx: usize, color: CardColor) -> f64 {
+        let seat = self.seat(player_idx);
+        let color_mask = packed::color_mask(color);
+
+        if (seat.known.present() | seat.holds) & color_mask != 0 {
+            return 1.0;
+        }
+        if seat.excluded & color_mask != 0 {
+            return 0.0;
+        }
+
+        let unknown = seat.hand_size.saturating_sub(seat.known.len());
+        1.0 - probability_of_none(
+            self.count_unseen(ALL_CARD_TYPES & !seat.excluded),
+            self.count_unseen(color_mask),
+            unknown,
+        )
+    }
+
+    /// The probability that the top card of the drawing deck can be played on the discard pile
+    /// as it is now.
+    pub fn probability_next_draw_playable(&self) -> f64 {
+        let Some(top_card) = self.top_card else {
+            return 0.0;
+        };
+
+        let num_unseen = self.num_unseen();
+        if num_unseen == 0 {
+            return 0.0;
+        }
+
+        let playable = packed::playable_mask(top_card, self.active_color);
+        self.count_unseen(playable) as f64 / num_unseen as f64
+    }
+}
+
+fn full_deck_counts() -> [u8; NUM_CARD_TYPES] {
+    let mut counts = [0; NUM_CARD_TYPES];
+    for card in Deck::standard().cards {
+        counts[PackedCard::new(card).index()] += 1;
+    }
+    counts
+}
+
+/// The probability that none of `draws` cards dealt from a pool of `pool` cards is one of the
+/// `matching` ones.
+fn probability_of_none(pool: usize, matching: usize, draws: usize) -> f64 {
+    (0..draws.min(pool))
+        .map(|i| (pool - matching).saturating_sub(i) as f64 / (pool - i) as f64)
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_executor::GameRuntime;
+    use crate::game_state::{CardKind, NormalCard, Numeric};
+    use crate::ruleset::Ruleset;
+
+    const DRAW_FOUR: Card = Card::Special(WildCard {
+        kind: WildCardKind::DrawFour,
+    });
+
+    fn red(numeric: Numeric) -> Card {
+        Card::Normal(NormalCard {
+            kind: CardKind::Numeric(numeric),
+            color: CardColor::Red,
+        })
+    }
+
+    fn tracker_after_draw_four_on_red() -> CardTracker {
+        let mut tracker = CardTracker::new();
+        for player_idx in 0..2 {
+            tracker.observe_event(&Event::Drew {
+                player_idx,
+                amount: 7,
+            });
+        }
+        tracker.observe_event(&Event::Played {
+            player_idx: 1,
+            card: red(Numeric::Five),
+        });
+        tracker.observe_event(&Event::Played {
+            player_idx: 1,
+            card: DRAW_FOUR,
+        });
+        tracker
+    }
+
+    #[test]
+    fn unseen_cards_are_everything_but_the_hand_and_the_discard_pile() {
+        let game_runtime = GameRuntime::from_seed(3, Ruleset::default(), 4);
+        let view = PlayerView::new(&game_runtime, 0);
+        let mut tracker = CardTracker::new();
+        tracker.update(&view);
+
+        assert_eq!(tracker.num_unseen(), 84 - 7 - 1);
+        let total: f64 = tracker.unseen_distribution().iter().map(|&(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let own_draw_fours = view.hand.iter().filter(|&&card| card == DRAW_FOUR).count();
+        let discarded_draw_fours = (view.table.top_card() == DRAW_FOUR) as usize;
+        assert_eq!(
+            tracker.remaining(DRAW_FOUR),
+            4 - own_draw_fours - discarded_draw_fours
+        );
+    }
+
+    #[test]
+    fn next_draw_is_playable_in_proportion_to_the_unseen_matches() {
+        let game_runtime = GameRuntime::from_seed(2, Ruleset::default(), 9);
+        let view = PlayerView::new(&game_runtime, 1);
+        let mut tracker = CardTracker::new();
+        tracker.update(&view);
+
+        let top_card = view.table.top_card();
+        let playable = tracker
+            .unseen_distribution()
+            .into_iter()
+            .filter(|&(card, _)| {
+                card.get_color().is_none()
+                    || card.get_color() == view.table.active_color.or(top_card.get_color())
+                    || matches!((card, top_card), (Card::Normal(a), Card::Normal(b)) if a.kind == b.kind)
+            })
+            .map(|(_, p)| p)
+            .sum::<f64>();
+
+        assert!((tracker.probability_next_draw_playable() - playable).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bluff_calls_settle_whether_the_bluffer_holds_the_color() {
+        let mut tracker = tracker_after_draw_four_on_red();
+        let before = tracker.probability_holds_color(1, CardColor::Red);
+        assert!(before > 0.0 && before < 1.0);
+        tracker.observe_event(&Event::CalledBluff {
+            player_idx: 0,
+            succeeded: false,
+        });
+        assert_eq!(tracker.probability_holds_color(1, CardColor::Red), 0.0);
+        assert_eq!(tracker.expected_in_hand(1, red(Numeric::One)), 0.0);
+
+        // Whatever the bluffer draws next could be red again.
+        tracker.observe_event(&Event::Drew {
+            player_idx: 1,
+            amount: 1,
+        });
+        assert!(tracker.probability_holds_color(1, CardColor::Red) > 0.0);
+
+        let mut tracker = tracker_after_draw_four_on_red();
+        tracker.observe_event(&Event::CalledBluff {
+            player_idx: 0,
+            succeeded: true,
+        });
+        tracker.observe_event(&Event::Drew {
+            player_idx: 1,
+            amount: 4,
+        });
+        assert_eq!(tracker.probability_holds_color(1, CardColor::Red), 1.0);
+    }
+
+    #[test]
+    fn revealed_cards_count_as_seen() {
+        let mut tracker = tracker_after_draw_four_on_red();
+        let hand = [red(Numeric::Two), DRAW_FOUR, red(Numeric::Two)];
+        tracker.reveal(1, &hand);
+
+        assert_eq!(tracker.num_unseen(), 84 - 2 - 3);
+        assert_eq!(tracker.unseen(red(Numeric::Two)), 0);
+        assert_eq!(tracker.remaining(red(Numeric::Two)), 2);
+        assert_eq!(tracker.expected_in_hand(1, red(Numeric::Two)), 2.0);
+        assert_eq!(tracker.probability_holds_color(1, CardColor::Red), 1.0);
+        assert_eq!(tracker.probability_holds_color(1, CardColor::Blue), 0.0);
+    }
+}
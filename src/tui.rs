@@ -0,0 +1,584 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, Stdout, Write};
+use std::rc::Rc;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, KeyCode, KeyEventKind};
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::agent::{Agent, AgentError};
+use crate::game_executor::Event;
+use crate::game_state::{Action, Card, CardColor, CardKind, Direction, WildCardKind};
+use crate::view::{OmniscientView, PlayerView};
+
+const CARD_WIDTH: u16 = 5;
+const CARD_HEIGHT: u16 = 3;
+const LOG_LINES: usize = 5;
+
+/// Restores the terminal when dropped, so a panic doesn't leave it in raw mode.
+struct TerminalGuard {
+    stdout: Stdout,
+}
+
+impl TerminalGuard {
+    fn enter() -> io::Result<TerminalGuard> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard { stdout })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, LeaveAlternateScreen, Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Something the player can select in the hand row: one of their cards, or a button for an
+/// action that doesn't involve a card from the hand.
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Choice {
+    Card(Card),
+    Button(Action),
+}
+
+impl Choice {
+    fn action(self) -> Action {
+        match self {
+            Choice::Card(card) => Action::Play { card },
+            Choice::Button(action) => action,
+        }
+    }
+}
+
+fn choices_for_hand(hand: &[Card], legal_actions: &[Action]) -> Vec<Choice> {
+    hand.iter()
+        .map(|&card| Choice::Card(card))
+        .chain(
+            legal_actions
+                .iter()
+                .filter(|action| {
+                    !matches!(action, Action::Play { .. } | Action::ChooseColor { .. })
+                })
+                .map(|&action| Choice::Button(action)),
+        )
+        .collect()
+}
+
+fn button_label(action: Action) -> &'static str {
+    match action {
+        Action::DrawCard { .. } => "Draw",
+        Action::CallBluff => "Call bluff",
+        Action::Pass => "Pass",
+        Action::Play { .. } => "Play",
+        Action::ChooseColor { .. } => "Choose color",
+    }
+}
+
+fn card_label(card: Card) -> String {
+    match card {
+        Card::Normal(card) => match card.kind {
+            CardKind::Numeric(numeric) => numeric.value().to_string(),
+        },
+        Card::Special(card) => match card.kind {
+            WildCardKind::Colorchooser => "W".to_owned(),
+            WildCardKind::DrawFour => "+4".to_owned(),
+        },
+    }
+}
+
+fn card_name(card: Card) -> String {
+    match card {
+        Card::Normal(normal_card) => {
+            format!("{} {}", color_name(normal_card.color), card_label(card))
+        }
+        Card::Special(wild_card) => match wild_card.kind {
+            WildCardKind::Colorchooser => "Wild".to_owned(),
+            WildCardKind::DrawFour => "Wild Draw Four".to_owned(),
+        },
+    }
+}
+
+fn describe_event(event: Event) -> String {
+    match event {
+        Event::Played { player_idx, card } => {
+            format!("P{} played {}", player_idx + 1, card_name(card))
+        }
+        Event::ChoseColor { player_idx, color } => {
+            format!("P{} chose {}", player_idx + 1, color_name(color))
+        }
+        Event::Drew {
+            player_idx,
+            amount: 1,
+        } => format!("P{} drew a card", player_idx + 1),
+        Event::Drew { player_idx, amount } => {
+            format!("P{} drew {} cards", player_idx + 1, amount)
+        }
+        Event::CalledBluff {
+            player_idx,
+            succeeded: true,
+        } => format!("P{} called a bluff and was right", player_idx + 1),
+        Event::CalledBluff {
+            player_idx,
+            succeeded: false,
+        } => format!("P{} called a bluff and was wrong", player_idx + 1),
+        Event::Passed { player_idx } => format!("P{} passed", player_idx + 1),
+        Event::ReshuffledDiscardPile => "The discard pile was reshuffled".to_owned(),
+        Event::Won { player_idx } => format!("P{} won", player_idx + 1),
+    }
+}
+
+fn terminal_color(color: Option<CardColor>) -> Color {
+    match color {
+        Some(CardColor::Blue) => Color::Blue,
+        Some(CardColor::Green) => Color::Green,
+        Some(CardColor::Yellow) => Color::Yellow,
+        Some(CardColor::Red) => Color::Red,
+        None => Color::DarkGrey,
+    }
+}
+
+fn color_name(color: CardColor) -> &'static str {
+    match color {
+        CardColor::Blue => "Blue",
+        CardColor::Green => "Green",
+        CardColor::Yellow => "Yellow",
+        CardColor::Red => "Red",
+    }
+}
+
+struct Screen {
+    guard: TerminalGuard,
+    hot_seat: bool,
+    last_seen_player_idx: Option<usize>,
+    selected: usize,
+    selected_color: usize,
+    message: Option<String>,
+    log: VecDeque<String>,
+}
+
+/// A human player at this terminal. Several of them can share the terminal for hot-seat play,
+/// in which case the table is hidden between their turns.
+pub struct TuiAgent {
+    player_idx: usize,
+    logs_events: bool,
+    screen: Rc<RefCell<Screen>>,
+}
+
+pub fn human_agents(seats: &[usize]) -> io::Result<Vec<TuiAgent>> {
+    let screen = Rc::new(RefCell::new(Screen {
+        guard: TerminalGuard::enter()?,
+        hot_seat: seats.len() > 1,
+        last_seen_player_idx: None,
+        selected: 0,
+        selected_color: 0,
+        message: None,
+        log: VecDeque::new(),
+    }));
+
+    Ok(seats
+        .iter()
+        .enumerate()
+        .map(|(idx, &player_idx)| TuiAgent {
+            player_idx,
+            // Every agent observes every action, so only one of them writes to the shared log.
+            logs_events: idx == 0,
+            screen: Rc::clone(&screen),
+        })
+        .collect())
+}
+
+impl Agent for TuiAgent {
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        legal_actions: &[Action],
+    ) -> Result<Action, AgentError> {
+        let mut screen = self.screen.borrow_mut();
+
+        if screen.last_seen_player_idx != Some(self.player_idx) {
+            screen.selected = 0;
+            if screen.hot_seat && !screen.hand_over(self.player_idx)? {
+                return Err(AgentError::Left);
+            }
+            screen.last_seen_player_idx = Some(self.player_idx);
+        }
+
+        let choosing_color = matches!(legal_actions.first(), Some(Action::ChooseColor { .. }));
+        let choices = choices_for_hand(&view.hand, legal_actions);
+
+        loop {
+            screen.draw(view, legal_actions)?;
+
+            let action = match read_key()? {
+                KeyCode::Char('q') | KeyCode::Esc => return Err(AgentError::Left),
+                KeyCode::Left if choosing_color => {
+                    screen.selected_color = (screen.selected_color + 3) % 4;
+                    None
+                }
+                KeyCode::Right if choosing_color => {
+                    screen.selected_color = (screen.selected_color + 1) % 4;
+                    None
+                }
+                KeyCode::Enter if choosing_color => Some(Action::ChooseColor {
+                    color: CardColor::ALL[screen.selected_color],
+                }),
+                KeyCode::Left => {
+                    screen.selected = screen.selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Right => {
+                    screen.selected = (screen.selected + 1).min(choices.len().saturating_sub(1));
+                    None
+                }
+                KeyCode::Enter => Some(choices[screen.selected.min(choices.len() - 1)].action()),
+                _ => None,
+            };
+
+            match action {
+                Some(action) if legal_actions.contains(&action) => {
+                    screen.message = None;
+                    return Ok(action);
+                }
+                Some(_) => {
+                    screen.message = Some("You can't play that card right now.".to_owned());
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn observe_event(&mut self, event: &Event) {
+        if !self.logs_events {
+            return;
+        }
+
+        let mut screen = self.screen.borrow_mut();
+        if screen.log.len() == LOG_LINES {
+            screen.log.pop_front();
+        }
+        screen.log.push_back(describe_event(*event));
+    }
+
+    fn observe_game_over(&mut self, view: &OmniscientView, winner: usize) {
+        if !self.logs_events {
+            return;
+        }
+
+        if self
+            .screen
+            .borrow_mut()
+            .draw_game_over(view, winner)
+            .is_ok()
+        {
+            let _ = read_key();
+        }
+    }
+}
+
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let event::Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(key.code);
+            }
+        }
+    }
+}
+
+impl Screen {
+    /// Hides the table between turns so the next player doesn't see the previous one's hand.
+    /// Returns `false` if the player asked to quit.
+    fn hand_over(&mut self, player_idx: usize) -> io::Result<bool> {
+        let out = &mut self.guard.stdout;
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(2, 2),
+            Print(format!("Player {}'s turn.", player_idx + 1)),
+            MoveTo(2, 4),
+            Print("Press Enter when you're ready, or q to quit."),
+        )?;
+        out.flush()?;
+
+        loop {
+            match read_key()? {
+                KeyCode::Enter => return Ok(true),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, view: &PlayerView, legal_actions: &[Action]) -> io::Result<()> {
+        let (width, _) = terminal::size()?;
+        let out = &mut self.guard.stdout;
+        let game_state = &view.game_state;
+
+        queue!(out, Clear(ClearType::All), MoveTo(2, 1), Print("UNO"))?;
+
+        let arrow = match game_state.direction {
+            Direction::Forward => " → ",
+            Direction::Backward => " ← ",
+        };
+
+        queue!(out, MoveTo(2, 3))?;
+        for (idx, hand_size) in view.table.hand_sizes.iter().enumerate() {
+            if idx > 0 {
+                queue!(out, Print(arrow))?;
+            }
+
+            if idx == view.table.current_player_idx {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+
+            queue!(
+                out,
+                Print(format!(" P{} [{}] ", idx + 1, hand_size)),
+                SetAttribute(Attribute::Reset),
+            )?;
+        }
+
+        draw_card(out, 4, 6, view.table.top_card(), false)?;
+        draw_card_back(out, 12, 6)?;
+        queue!(
+            out,
+            MoveTo(4, 9),
+            Print("Discard"),
+            MoveTo(12, 9),
+            Print(format!("Draw ({})", view.table.drawing_deck_len)),
+        )?;
+
+        if let Some(color) = view.table.active_color {
+            queue!(
+                out,
+                MoveTo(24, 7),
+                Print("Color: "),
+                SetForegroundColor(terminal_color(Some(color))),
+                Print(color_name(color)),
+                ResetColor,
+            )?;
+        }
+
+        for (idx, line) in self.log.iter().enumerate() {
+            queue!(out, MoveTo(44, 5 + idx as u16), Print(line))?;
+        }
+
+        let hand = &view.hand;
+        let choices = choices_for_hand(hand, legal_actions);
+        let cards_per_row = ((width.saturating_sub(4)) / (CARD_WIDTH + 1)).max(1) as usize;
+        let hand_top = 12;
+
+        queue!(
+            out,
+            MoveTo(2, hand_top - 1),
+            Print(format!("Player {}'s hand", view.player_idx + 1)),
+        )?;
+
+        for (idx, &card) in hand.iter().enumerate() {
+            let row = (idx / cards_per_row) as u16;
+            let column = (idx % cards_per_row) as u16;
+            let selected = idx == self.selected;
+            let dimmed = !legal_actions.contains(&Action::Play { card });
+            let x = 2 + column * (CARD_WIDTH + 1);
+            let y = hand_top + 1 + row * (CARD_HEIGHT + 1) - selected as u16;
+            draw_card(out, x, y, card, dimmed)?;
+        }
+
+        let rows = hand.len().div_ceil(cards_per_row) as u16;
+        let buttons_top = hand_top + 1 + rows * (CARD_HEIGHT + 1);
+        queue!(out, MoveTo(2, buttons_top))?;
+
+        for (idx, choice) in choices.iter().enumerate().skip(hand.len()) {
+            if let Choice::Button(action) = *choice {
+                if idx == self.selected {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                queue!(
+                    out,
+                    Print(format!("[ {} ]", button_label(action))),
+                    SetAttribute(Attribute::Reset),
+                    Print("  "),
+                )?;
+            }
+        }
+
+        if matches!(legal_actions.first(), Some(Action::ChooseColor { .. })) {
+            self.draw_color_popup(buttons_top + 2)?;
+        }
+
+        let out = &mut self.guard.stdout;
+        if let Some(message) = &self.message {
+            queue!(out, MoveTo(2, buttons_top + 8), Print(message))?;
+        }
+
+        queue!(
+            out,
+            MoveTo(2, buttons_top + 9),
+            SetAttribute(Attribute::Dim),
+            Print("←/→ select   Enter confirm   q quit"),
+            SetAttribute(Attribute::Reset),
+        )?;
+
+        out.flush()
+    }
+
+    fn draw_game_over(&mut self, view: &OmniscientView, winner: usize) -> io::Result<()> {
+        let out = &mut self.guard.stdout;
+        queue!(
+            out,
+            Clear(ClearType::All),
+            MoveTo(2, 1),
+            Print(format!("Player {} wins!", winner + 1)),
+        )?;
+
+        for (player_idx, hand) in view.hands.iter().enumerate() {
+            let y = 3 + player_idx as u16 * (CARD_HEIGHT + 1);
+            queue!(out, MoveTo(2, y + 1), Print(format!("P{}", player_idx + 1)))?;
+
+            for (idx, &card) in hand.iter().enumerate() {
+                draw_card(out, 6 + idx as u16 * (CARD_WIDTH + 1), y, card, false)?;
+            }
+        }
+
+        queue!(
+            out,
+            MoveTo(2, 4 + view.hands.len() as u16 * (CARD_HEIGHT + 1)),
+            Print("Press any key to exit."),
+        )?;
+
+        out.flush()
+    }
+
+    fn draw_color_popup(&mut self, top: u16) -> io::Result<()> {
+        let out = &mut self.guard.stdout;
+        queue!(
+            out,
+            MoveTo(2, top),
+            Print("┌ Choose a color ──────────────────────┐"),
+            MoveTo(2, top + 1),
+            Print("│                                      │"),
+            MoveTo(2, top + 2),
+            Print("│                                      │"),
+            MoveTo(2, top + 3),
+            Print("└──────────────────────────────────────┘"),
+            MoveTo(4, top + 2),
+        )?;
+
+        for (idx, &color) in CardColor::ALL.iter().enumerate() {
+            if idx == self.selected_color {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                out,
+                SetForegroundColor(terminal_color(Some(color))),
+                Print(format!(" {:<6} ", color_name(color))),
+                ResetColor,
+                SetAttribute(Attribute::Reset),
+                Print(" "),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn draw_card(out: &mut Stdout, x: u16, y: u16, card: Card, dimmed: bool) -> io::Result<()> {
+    let label = format!("{:^3}", card_label(card));
+    draw_box(out, x, y, &label, terminal_color(card.get_color()), dimmed)
+}
+
+fn draw_card_back(out: &mut Stdout, x: u16, y: u16) -> io::Result<()> {
+    draw_box(out, x, y, "UNO", Color::Black, false)
+}
+
+fn draw_box(
+    out: &mut Stdout,
+    x: u16,
+    y: u16,
+    label: &str,
+    background: Color,
+    dimmed: bool,
+) -> io::Result<()> {
+    queue!(
+        out,
+        SetBackgroundColor(background),
+        SetForegroundColor(Color::White)
+    )?;
+    if dimmed {
+        queue!(out, SetAttribute(Attribute::Dim))?;
+    }
+    queue!(
+        out,
+        MoveTo(x, y),
+        Print("┌───┐"),
+        MoveTo(x, y + 1),
+        Print(format!("│{}│", label)),
+        MoveTo(x, y + 2),
+        Print("└───┘"),
+        SetAttribute(Attribute::Reset),
+        ResetColor,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::*;
+
+    #[test]
+    fn test_choices_list_hand_cards_before_buttons() {
+        let red_one_card = Card::Normal(NormalCard {
+            kind: CardKind::Numeric(Numeric::One),
+            color: CardColor::Red,
+        });
+
+        let draw_four_card = Card::Special(WildCard {
+            kind: WildCardKind::DrawFour,
+        });
+
+        let legal_actions = vec![
+            Action::DrawCard {
+                amount: DrawAmount::One,
+            },
+            Action::Play {
+                card: draw_four_card,
+            },
+        ];
+
+        assert_eq!(
+            choices_for_hand(&[red_one_card, draw_four_card], &legal_actions),
+            vec![
+                Choice::Card(red_one_card),
+                Choice::Card(draw_four_card),
+                Choice::Button(Action::DrawCard {
+                    amount: DrawAmount::One
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_card_labels() {
+        assert_eq!(
+            card_label(Card::Normal(NormalCard {
+                kind: CardKind::Numeric(Numeric::Seven),
+                color: CardColor::Green,
+            })),
+            "7"
+        );
+        assert_eq!(
+            card_label(Card::Special(WildCard {
+                kind: WildCardKind::DrawFour,
+            })),
+            "+4"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use alloc::vec::Vec;
+
+use crate::game_executor::{Event, GameRuntime};
+use crate::game_state::{Card, CardColor, Direction, GameState};
+use crate::ruleset::Ruleset;
+
+/// What everybody at the table can see, spectators included: the discard pile, how many cards
+/// each player holds and everything that has happened so far, but no card in anybody's hand.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SpectatorView<'a> {
+    pub current_player_idx: usize,
+    pub hand_sizes: Vec<usize>,
+    /// The game state with the card the current player just drew hidden.
+    pub(crate) game_state: GameState,
+    pub discard_pile: &'a [Card],
+    pub active_color: Option<CardColor>,
+    pub drawing_deck_len: usize,
+    pub events: &'a [Event],
+    pub winner: Option<usize>,
+    pub ruleset: Ruleset,
+}
+
+impl<'a> SpectatorView<'a> {
+    pub fn new(game_runtime: &'a GameRuntime) -> SpectatorView<'a> {
+        SpectatorView {
+            current_player_idx: game_runtime.current_player_idx(),
+            hand_sizes: (0..game_runtime.num_players())
+                .map(|player_idx| game_runtime.packed_hand(player_idx).len())
+                .collect(),
+            game_state: GameState {
+                last_drew_card: None,
+                ..*game_runtime.game_state()
+            },
+            discard_pile: game_runtime.discard_pile(),
+            active_color: game_runtime.active_color(),
+            drawing_deck_len: game_runtime.drawing_deck_len(),
+            events: game_runtime.events(),
+            winner: game_runtime.winner(),
+            ruleset: game_runtime.ruleset(),
+        }
+    }
+
+    pub fn top_card(&self) -> Card {
+        self.game_state.last_played_card
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.game_state.direction
+    }
+}
+
+/// What one player can see: the table plus their own hand.
+#[derive(PartialEq, Clone, Debug)]
+pub struct PlayerView<'a> {
+    pub player_idx: usize,
+    /// Sorted by type.
+    pub hand: Vec<Card>,
+    /// The card this player just drew is only visible to them.
+    pub(crate) game_state: GameState,
+    pub table: SpectatorView<'a>,
+}
+
+impl<'a> PlayerView<'a> {
+    pub fn new(game_runtime: &'a GameRuntime, player_idx: usize) -> PlayerView<'a> {
+        let table = SpectatorView::new(game_runtime);
+        let game_state = if player_idx == table.current_player_idx {
+            *game_runtime.game_state()
+        } else {
+            table.game_state
+        };
+
+        PlayerView {
+            player_idx,
+            hand: game_runtime.hand(player_idx),
+            game_state,
+            table,
+        }
+    }
+
+    /// The card this player drew on their turn, until they play or pass it.
+    pub fn drawn_card(&self) -> Option<Card> {
+        self.game_state.last_drew_card
+    }
+}
+
+/// Every card in the game, for replays and debugging. Never hand this to an agent.
+#[derive(PartialEq, Clone, Debug)]
+pub struct OmniscientView<'a> {
+    /// Each sorted by type.
+    pub hands: Vec<Vec<Card>>,
+    pub drawing_deck: &'a [Card],
+    pub(crate) game_state: GameState,
+    pub table: SpectatorView<'a>,
+}
+
+impl<'a> OmniscientView<'a> {
+    pub fn new(game_runtime: &'a GameRuntime) -> OmniscientView<'a> {
+        OmniscientView {
+            hands: (0..game_runtime.num_players())
+                .map(|player_idx| game_runtime.hand(player_idx))
+                .collect(),
+            drawing_deck: game_runtime.drawing_deck(),
+            game_state: *game_runtime.game_state(),
+            table: SpectatorView::new(game_runtime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game_state::{Action, DrawAmount};
+
+    #[test]
+    fn player_view_only_shows_own_hand() {
+        let game_runtime = GameRuntime::new(3, Ruleset::default(), StdRng::seed_from_u64(7));
+        let view = PlayerView::new(&game_runtime, 1);
+
+        assert_eq!(view.hand, game_runtime.hand(1));
+        assert_eq!(view.table.hand_sizes, vec![7, 7, 7]);
+        assert_eq!(view.table.current_player_idx, 0);
+        assert_eq!(view.table.top_card(), game_runtime.discard_pile()[0]);
+    }
+
+    #[test]
+    fn drawn_card_is_only_visible_to_the_player_who_drew_it() {
+        let mut game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(7));
+        game_runtime
+            .execute_action(Action::DrawCard {
+                amount: DrawAmount::One,
+            })
+            .unwrap();
+
+        let drawn_card = game_runtime.game_state().last_drew_card;
+        assert!(drawn_card.is_some());
+
+        assert_eq!(
+            PlayerView::new(&game_runtime, 0).game_state.last_drew_card,
+            drawn_card
+        );
+        assert_eq!(
+            PlayerView::new(&game_runtime, 1).game_state.last_drew_card,
+            None
+        );
+        assert_eq!(
+            SpectatorView::new(&game_runtime).game_state.last_drew_card,
+            None
+        );
+        assert_eq!(
+            SpectatorView::new(&game_runtime).events,
+            &[Event::Drew {
+                player_idx: 0,
+                amount: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn omniscient_view_shows_every_card() {
+        let game_runtime = GameRuntime::new(2, Ruleset::default(), StdRng::seed_from_u64(7));
+        let view = OmniscientView::new(&game_runtime);
+
+        let total_cards: usize = view.hands.iter().map(|hand| hand.len()).sum::<usize>()
+            + view.drawing_deck.len()
+            + view.table.discard_pile.len();
+
+        assert_eq!(total_cards, 84);
+    }
+}
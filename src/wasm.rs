@@ -0,0 +1,200 @@
+use wasm_bindgen::prelude::*;
+
+use crate::agent::Agent;
+use crate::bots::{self, BOT_NAMES};
+use crate::game_executor::GameRuntime;
+use crate::game_state::Action;
+use crate::notation;
+use crate::protocol::TableView;
+use crate::ruleset::Ruleset;
+use crate::view::PlayerView;
+
+/// A game for a web page to run on its own. The page plays its seats with `applyAction` and
+/// leaves the others to bots, which take their turns in `playBots`.
+///
+/// Actions and events are written in the notation of the engine protocol (`r5`, `w4`, `red`,
+/// `draw1`, `pass`, ...), views are JSON, and errors are thrown as messages.
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame {
+    game_runtime: GameRuntime,
+    bots: Vec<Option<Box<dyn Agent>>>,
+    seed: u64,
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    /// `ruleset` is one of the ruleset names, such as `"standard"` or `"quick"`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_players: usize, ruleset: &str, seed: u32) -> Result<WasmGame, String> {
+        let ruleset =
+            Ruleset::by_name(ruleset).ok_or_else(|| format!("unknown ruleset {}", ruleset))?;
+        if !(2..=ruleset.max_players()).contains(&num_players) {
+            return Err(format!(
+                "a game needs between 2 and {} players",
+                ruleset.max_players()
+            ));
+        }
+
+        Ok(WasmGame {
+            game_runtime: GameRuntime::from_seed(num_players, ruleset, seed as u64),
+            bots: (0..num_players).map(|_| None).collect(),
+            seed: seed as u64,
+        })
+    }
+
+    /// Hands `seat` over to one of the built-in bots, such as `"heuristic"`.
+    #[wasm_bindgen(js_name = setBot)]
+    pub fn set_bot(&mut self, seat: usize, name: &str) -> Result<(), String> {
+        if seat >= self.bots.len() {
+            return Err(format!("there is no seat {}", seat));
+        }
+
+        // External engines need a process to run in.
+        if !BOT_NAMES.contains(&name) {
+            return Err(format!("unknown bot {}", name));
+        }
+
+        self.bots[seat] = Some(bots::bot_by_name(name, self.seed + seat as u64)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter, js_name = numPlayers)]
+    pub fn num_players(&self) -> usize {
+        self.game_runtime.num_players()
+    }
+
+    #[wasm_bindgen(getter, js_name = currentPlayer)]
+    pub fn current_player(&self) -> usize {
+        self.game_runtime.current_player_idx()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn winner(&self) -> Option<usize> {
+        self.game_runtime.winner()
+    }
+
+    #[wasm_bindgen(js_name = legalActions)]
+    pub fn legal_actions(&self) -> Vec<String> {
+        self.game_runtime
+            .legal_actions()
+            .into_iter()
+            .map(notation::format_action)
+            .collect()
+    }
+
+    /// Takes `action` for the current player, whether a bot sits there or not.
+    #[wasm_bindgen(js_name = applyAction)]
+    pub fn apply_action(&mut self, action: &str) -> Result<(), String> {
+        let action =
+            notation::parse_action(action).ok_or_else(|| format!("unknown action {}", action))?;
+        self.execute(action)
+    }
+
+    /// Lets the bots play until it is the turn of a seat without one, or the game is over, and
+    /// returns the actions they took.
+    #[wasm_bindgen(js_name = playBots)]
+    pub fn play_bots(&mut self) -> Result<Vec<String>, String> {
+        let mut actions = vec![];
+
+        while self.game_runtime.winner().is_none() {
+            let player_idx = self.game_runtime.current_player_idx();
+            let Some(bot) = self.bots[player_idx].as_mut() else {
+                break;
+            };
+
+            let legal_actions = self.game_runtime.legal_actions();
+            let view = PlayerView::new(&self.game_runtime, player_idx);
+            let action = bot
+                .choose_action(&view, &legal_actions)
+                .map_err(|err| err.to_string())?;
+
+            self.execute(action)?;
+            actions.push(notation::format_action(action));
+        }
+
+        Ok(actions)
+    }
+
+    /// What `seat` can see, as JSON.
+    pub fn view(&self, seat: usize) -> Result<String, String> {
+        if seat >= self.game_runtime.num_players() {
+            return Err(format!("there is no seat {}", seat));
+        }
+
+        let view = PlayerView::new(&self.game_runtime, seat);
+        serde_json::to_string(&TableView::new(&view)).map_err(|err| err.to_string())
+    }
+
+    /// Everything that has happened since the first `since` events, e.g. `played 0 r5`.
+    pub fn events(&self, since: usize) -> Vec<String> {
+        self.game_runtime
+            .events()
+            .iter()
+            .skip(since)
+            .map(|&event| notation::format_event(event))
+            .collect()
+    }
+}
+
+impl WasmGame {
+    fn execute(&mut self, action: Action) -> Result<(), String> {
+        let seen_events = self.game_runtime.events().len();
+        self.game_runtime
+            .execute_action(action)
+            .map_err(|err| err.to_string())?;
+
+        for event in &self.game_runtime.events()[seen_events..] {
+            for bot in self.bots.iter_mut().flatten() {
+                bot.observe_event(event);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bots_play_a_game_out() {
+        let mut game = WasmGame::new(3, "standard", 5).unwrap();
+        for seat in 0..3 {
+            game.set_bot(seat, "heuristic").unwrap();
+        }
+
+        let actions = game.play_bots().unwrap();
+
+        assert!(game.winner().is_some());
+        assert!(game.legal_actions().is_empty());
+        assert!(actions.len() <= game.events(0).len());
+    }
+
+    #[test]
+    fn bots_stop_at_the_pages_seats() {
+        let mut game = WasmGame::new(2, "quick", 1).unwrap();
+        game.set_bot(1, "greedy").unwrap();
+
+        while game.winner().is_none() {
+            assert_eq!(game.current_player(), 0);
+            let action = game.legal_actions()[0].clone();
+            game.apply_action(&action).unwrap();
+            game.play_bots().unwrap();
+        }
+    }
+
+    #[test]
+    fn bad_input_is_rejected() {
+        assert!(WasmGame::new(1, "standard", 0).is_err());
+        assert!(WasmGame::new(2, "calvinball", 0).is_err());
+
+        let mut game = WasmGame::new(2, "standard", 0).unwrap();
+        assert!(game.set_bot(2, "greedy").is_err());
+        assert!(game.set_bot(0, "engine:./bot").is_err());
+        assert!(game.apply_action("r10").is_err());
+        assert!(game.apply_action("bluff").is_err());
+        assert!(game.view(2).is_err());
+        assert!(game.view(0).unwrap().contains("\"seat\":0"));
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::protocol::ServerMessage;
+use crate::server::{HubHandle, ACCEPT_RETRY_INTERVAL};
+
+/// The web client, built into the binary so the server runs without any files around.
+const STATIC_FILES: [(&str, &str, &str); 3] = [
+    ("/", "text/html", include_str!("../web/index.html")),
+    (
+        "/client.js",
+        "text/javascript",
+        include_str!("../web/client.js"),
+    ),
+    ("/style.css", "text/css", include_str!("../web/style.css")),
+];
+
+/// Where browsers open their WebSocket.
+const WEBSOCKET_PATH: &str = "/ws";
+
+/// Requests with bigger headers than this are turned away.
+const MAX_HEADER_LEN: u64 = 16 * 1024;
+
+/// How long a WebSocket connection waits for the browser before checking on messages from the
+/// hub, since both have to be handled on the same thread.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The start of an HTTP request, which is all a static file or a WebSocket handshake needs.
+struct Request {
+    method: String,
+    path: String,
+    /// Keyed by lowercase name.
+    headers: HashMap<String, String>,
+}
+
+impl Request {
+    fn read(reader: &mut impl BufRead) -> io::Result<Request> {
+        let mut reader = reader.take(MAX_HEADER_LEN);
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(ErrorKind::InvalidData, "incomplete request"));
+            }
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            lines.push(line.to_owned());
+        }
+
+        let mut request_line = lines.first().map(String::as_str).unwrap_or("").split(' ');
+        let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+            return Err(io::Error::new(ErrorKind::InvalidData, "malformed request"));
+        };
+
+        let headers = lines[1..]
+            .iter()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_owned()))
+            .collect();
+
+        Ok(Request {
+            method: method.to_owned(),
+            path: path.to_owned(),
+            headers,
+        })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    fn is_websocket_upgrade(&self) -> bool {
+        self.header("upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+    }
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// Serves the web client, and lets browsers into the lobby over a WebSocket speaking the same
+/// JSON messages as TCP clients, one message per text frame.
+pub struct WebServer {
+    listener: TcpListener,
+}
+
+impl WebServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<WebServer> {
+        Ok(WebServer {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections for good. Failing to accept one is only logged.
+    pub fn run(self, hub: HubHandle) {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("server: {}", err);
+                    thread::sleep(ACCEPT_RETRY_INTERVAL);
+                    continue;
+                }
+            };
+            let hub = hub.clone();
+
+            // A browser that goes away halfway through a request is no concern of the others.
+            thread::spawn(move || {
+                let _ = serve(stream, hub);
+            });
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, hub: HubHandle) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = Request::read(&mut reader)?;
+
+    if request.method != "GET" {
+        return write_response(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed",
+        );
+    }
+
+    if request.path == WEBSOCKET_PATH {
+        let Some(key) = request
+            .header("sec-websocket-key")
+            .filter(|_| request.is_websocket_upgrade())
+        else {
+            return write_response(
+                &mut stream,
+                "400 Bad Request",
+                "text/plain",
+                "expected a websocket upgrade",
+            );
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Accept: {}\r\n\r\n",
+            derive_accept_key(key.as_bytes())
+        )?;
+
+        // Browsers wait for the handshake before sending anything, but whatever got buffered
+        // belongs to the WebSocket all the same.
+        let already_read = reader.buffer().to_vec();
+        let websocket = WebSocket::from_partially_read(stream, already_read, Role::Server, None);
+        return run_websocket(websocket, hub);
+    }
+
+    // Query strings don't change what gets served.
+    let path = request.path.split('?').next().unwrap_or("/");
+    match STATIC_FILES
+        .iter()
+        .find(|(file_path, _, _)| *file_path == path)
+    {
+        Some((_, content_type, body)) => write_response(&mut stream, "200 OK", content_type, body),
+        None => write_response(&mut stream, "404 Not Found", "text/plain", "not found"),
+    }
+}
+
+/// Passes messages between the browser and the hub until either goes away.
+fn run_websocket(mut websocket: WebSocket<TcpStream>, hub: HubHandle) -> io::Result<()> {
+    websocket.get_mut().set_nodelay(true)?;
+    websocket.get_mut().set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let (outbox, inbox) = mpsc::channel::<ServerMessage>();
+    let connection_id = hub.connect(outbox);
+
+    let result = loop {
+        match inbox.try_recv() {
+            Ok(message) => {
+                let text = serde_json::to_string(&message).unwrap();
+                if let Err(err) = websocket.send(Message::text(text)) {
+                    break Err(err);
+                }
+                continue;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => break Ok(()),
+        }
+
+        match websocket.read() {
+            Ok(Message::Text(text)) => {
+                let message = serde_json::from_str(&text).map_err(|err| err.to_string());
+                if !hub.receive(connection_id, message) {
+                    break Ok(());
+                }
+            }
+            Ok(Message::Close(_)) => break Ok(()),
+            // Pings are answered by tungstenite itself, and nothing is sent as binary.
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(err) => break Err(err),
+        }
+    };
+
+    hub.disconnect(connection_id);
+
+    match result {
+        Ok(())
+        | Err(tungstenite::Error::ConnectionClosed)
+        | Err(tungstenite::Error::AlreadyClosed) => Ok(()),
+        Err(tungstenite::Error::Io(err)) => Err(err),
+        Err(err) => Err(io::Error::other(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tungstenite::stream::MaybeTlsStream;
+
+    use super::*;
+    use crate::game_state::Action;
+    use crate::protocol::ClientMessage;
+    use crate::server::ServerConfig;
+
+    type Client = WebSocket<MaybeTlsStream<TcpStream>>;
+
+    fn start_web_server() -> SocketAddr {
+        let web_server = WebServer::bind("127.0.0.1:0").unwrap();
+        let addr = web_server.local_addr().unwrap();
+        let hub = HubHandle::spawn(ServerConfig {
+            seed: 5,
+            ..ServerConfig::default()
+        });
+
+        thread::spawn(move || web_server.run(hub));
+        addr
+    }
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, addr).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn connect(addr: SocketAddr) -> Client {
+        let (client, _) = tungstenite::connect(format!("ws://{}{}", addr, WEBSOCKET_PATH)).unwrap();
+        client
+    }
+
+    fn send(client: &mut Client, message: &ClientMessage) {
+        let text = serde_json::to_string(message).unwrap();
+        client.send(Message::text(text)).unwrap();
+    }
+
+    fn receive(client: &mut Client) -> ServerMessage {
+        loop {
+            if let Message::Text(text) = client.read().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn serves_the_web_client() {
+        let addr = start_web_server();
+
+        let page = get(addr, "/");
+        assert!(page.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(page.contains("text/html"));
+        assert!(page.contains("client.js"));
+
+        assert!(get(addr, "/client.js").starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(get(addr, "/nonsense").starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(get(addr, WEBSOCKET_PATH).starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[test]
+    fn browsers_play_over_websockets() {
+        let addr = start_web_server();
+        let mut client = connect(addr);
+
+        send(
+            &mut client,
+            &ClientMessage::CreateRoom {
+                ruleset: "standard".to_owned(),
+                seats: 2,
+            },
+        );
+        assert!(matches!(
+            receive(&mut client),
+            ServerMessage::Joined {
+                room_id: 1,
+                seat: 0,
+                ..
+            }
+        ));
+
+        send(
+            &mut client,
+            &ClientMessage::FillWithBots {
+                bot: "greedy".to_owned(),
+            },
+        );
+        send(&mut client, &ClientMessage::Ready { ready: true });
+
+        // Playing the last legal action never just draws and passes, so the game ends.
+        loop {
+            match receive(&mut client) {
+                ServerMessage::State { legal_actions, .. } if !legal_actions.is_empty() => {
+                    let action: Action = *legal_actions.last().unwrap();
+                    send(&mut client, &ClientMessage::Action { action });
+                }
+                ServerMessage::GameOver { .. } => break,
+                ServerMessage::Error { message } => panic!("server error: {}", message),
+                _ => {}
+            }
+        }
+
+        // Scores follow every finished game.
+        loop {
+            if let ServerMessage::Scores { scores } = receive(&mut client) {
+                assert_eq!(scores.len(), 2);
+                assert_eq!(scores.iter().map(|score| score.wins).sum::<usize>(), 1);
+                break;
+            }
+        }
+
+        // Lines that aren't messages get an error back, as over TCP.
+        client.send(Message::text("nonsense")).unwrap();
+        assert!(matches!(receive(&mut client), ServerMessage::Error { .. }));
+    }
+}
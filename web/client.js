@@ -0,0 +1,241 @@
+"use strict";
+
+// Talks to the server over a WebSocket, one JSON message per frame, the same messages TCP
+// clients send one per line.
+
+const $ = (id) => document.getElementById(id);
+
+const NUMERALS = {
+  zero: "0", one: "1", two: "2", three: "3", four: "4",
+  five: "5", six: "6", seven: "7", eight: "8", nine: "9",
+};
+
+let socket = null;
+let seat = null;
+let names = [];
+
+function send(message) {
+  socket.send(JSON.stringify(message));
+}
+
+function cardLabel(card) {
+  if (card.normal) {
+    return NUMERALS[card.normal.kind.numeric];
+  }
+  return card.special.kind === "draw_four" ? "+4" : "wild";
+}
+
+function cardColor(card) {
+  return card.normal ? card.normal.color : "";
+}
+
+function cardElement(card) {
+  const element = document.createElement("span");
+  element.className = `card ${cardColor(card)}`;
+  element.textContent = cardLabel(card);
+  return element;
+}
+
+function actionLabel(action) {
+  if (action === "call_bluff") return "Call bluff";
+  if (action === "pass") return "Pass";
+  if (action.play) return `Play ${cardColor(action.play.card)} ${cardLabel(action.play.card)}`;
+  if (action.choose_color) return `Choose ${action.choose_color.color}`;
+  if (action.draw_card) return `Draw ${action.draw_card.amount}`;
+  return JSON.stringify(action);
+}
+
+function playerName(idx) {
+  return names[idx] || `seat ${idx + 1}`;
+}
+
+function eventLabel(event) {
+  if (event === "reshuffled_discard_pile") return "The discard pile was reshuffled";
+  const [kind, body] = Object.entries(event)[0];
+  const who = playerName(body.player_idx);
+  switch (kind) {
+    case "played": return `${who} played ${cardColor(body.card)} ${cardLabel(body.card)}`;
+    case "chose_color": return `${who} chose ${body.color}`;
+    case "drew": return `${who} drew ${body.amount}`;
+    case "called_bluff": return `${who} called a bluff, ${body.succeeded ? "rightly" : "wrongly"}`;
+    case "passed": return `${who} passed`;
+    case "won": return `${who} won`;
+    default: return JSON.stringify(event);
+  }
+}
+
+function log(text) {
+  const item = document.createElement("li");
+  item.textContent = text;
+  $("log").append(item);
+  $("log").scrollTop = $("log").scrollHeight;
+}
+
+function showRooms(rooms) {
+  $("rooms").replaceChildren(...rooms.map((room) => {
+    const item = document.createElement("li");
+    const taken = room.seats.filter((seat) => seat).length;
+    item.textContent =
+      `Room ${room.room_id} (${room.ruleset}), ${taken}/${room.seats.length} seats` +
+      `${room.playing ? ", playing" : ""} `;
+
+    const join = document.createElement("button");
+    join.textContent = "Join";
+    join.disabled = taken === room.seats.length;
+    join.onclick = () => send({ type: "join_room", room_id: room.room_id, seat: null });
+
+    const watch = document.createElement("button");
+    watch.textContent = "Watch";
+    watch.onclick = () => send({ type: "spectate", room_id: room.room_id, omniscient: false });
+
+    item.append(join, watch);
+    return item;
+  }));
+}
+
+function showRoom(room) {
+  names = room.seats.map((seat) => seat && seat.name);
+  $("room-title").textContent = `Room ${room.room_id} (${room.ruleset})`;
+  $("seats-list").replaceChildren(...room.seats.map((summary, idx) => {
+    const item = document.createElement("li");
+    if (!summary) {
+      item.textContent = "(empty)";
+    } else {
+      const notes = [
+        summary.bot && "bot",
+        summary.away && "away",
+        !room.playing && summary.ready && "ready",
+        idx === seat && "you",
+      ].filter(Boolean);
+      item.textContent = summary.name + (notes.length ? ` (${notes.join(", ")})` : "");
+    }
+    return item;
+  }));
+  $("room-controls").hidden = room.playing || seat === null;
+}
+
+function enterRoom(roomId) {
+  $("lobby").hidden = true;
+  $("room").hidden = false;
+  $("log").replaceChildren();
+  $("ready").checked = false;
+  log(`Entered room ${roomId}`);
+}
+
+function showTable(view, legalActions) {
+  $("table-info").textContent =
+    `${playerName(view.current_player_idx)} to play` +
+    `${view.active_color ? `, color is ${view.active_color}` : ""}` +
+    ` · hands: ${view.hand_sizes.map((size, idx) => `${playerName(idx)} ${size}`).join(", ")}` +
+    ` · ${view.drawing_deck_len} cards left to draw`;
+  $("top-card").replaceChildren(cardElement(view.top_card));
+  $("hand").replaceChildren(...(view.hand || []).map(cardElement));
+  $("actions").replaceChildren(...legalActions.map((action) => {
+    const button = document.createElement("button");
+    button.textContent = actionLabel(action);
+    button.onclick = () => send({ type: "action", action });
+    return button;
+  }));
+}
+
+function handle(message) {
+  switch (message.type) {
+    case "rooms":
+      showRooms(message.rooms);
+      break;
+    case "joined":
+      seat = message.seat;
+      sessionStorage.setItem("token", message.token);
+      enterRoom(message.room_id);
+      break;
+    case "resumed":
+      seat = message.seat;
+      enterRoom(message.room_id);
+      message.missed_events.forEach((event) => log(eventLabel(event)));
+      break;
+    case "spectating":
+      seat = null;
+      enterRoom(message.room_id);
+      break;
+    case "left":
+      seat = null;
+      sessionStorage.removeItem("token");
+      $("room").hidden = true;
+      $("lobby").hidden = false;
+      send({ type: "list_rooms" });
+      break;
+    case "room":
+      showRoom(message.room);
+      break;
+    case "state":
+      showTable(message.view, message.legal_actions);
+      break;
+    case "table":
+      showTable(message.view, []);
+      break;
+    case "event":
+      log(eventLabel(message.event));
+      break;
+    case "timed_out":
+      log(`${playerName(message.seat)} ran out of time`);
+      break;
+    case "game_over":
+      log(`Game over: ${playerName(message.winner)} won ${message.points} points`);
+      $("actions").replaceChildren();
+      $("ready").checked = false;
+      break;
+    case "scores":
+      $("scores").replaceChildren(...message.scores.map((score) => {
+        const row = document.createElement("tr");
+        for (const value of [score.name, score.games, score.wins, score.points]) {
+          const cell = document.createElement("td");
+          cell.textContent = value;
+          row.append(cell);
+        }
+        return row;
+      }));
+      break;
+    case "error":
+      log(`Error: ${message.message}`);
+      if (message.message === "unknown or expired session") {
+        sessionStorage.removeItem("token");
+      }
+      break;
+  }
+}
+
+function connect() {
+  const protocol = location.protocol === "https:" ? "wss:" : "ws:";
+  socket = new WebSocket(`${protocol}//${location.host}/ws`);
+
+  socket.onopen = () => {
+    $("status").textContent = "connected";
+    const token = sessionStorage.getItem("token");
+    if (token) {
+      send({ type: "resume", token });
+    }
+    send({ type: "list_rooms" });
+    send({ type: "scores" });
+  };
+  socket.onmessage = (event) => handle(JSON.parse(event.data));
+  socket.onclose = () => {
+    $("status").textContent = "disconnected, retrying…";
+    setTimeout(connect, 2000);
+  };
+}
+
+$("name-form").onsubmit = (event) => {
+  event.preventDefault();
+  send({ type: "set_name", name: $("name").value });
+};
+$("create-form").onsubmit = (event) => {
+  event.preventDefault();
+  send({ type: "create_room", ruleset: $("ruleset").value, seats: Number($("seats").value) });
+};
+$("refresh").onclick = () => send({ type: "list_rooms" });
+$("scores-button").onclick = () => send({ type: "scores" });
+$("ready").onchange = () => send({ type: "ready", ready: $("ready").checked });
+$("fill").onclick = () => send({ type: "fill_with_bots", bot: $("bot").value });
+$("leave").onclick = () => send({ type: "leave_room" });
+
+connect();
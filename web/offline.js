@@ -0,0 +1,107 @@
+// Plays against bots with the engine running in the page, no server needed. Build the engine
+// into web/pkg first:
+//
+//   cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//   wasm-bindgen --target web --out-dir web/pkg target/wasm32-unknown-unknown/release/uno.wasm
+//
+// then serve web/ with any static file server and open offline.html.
+
+import init, { Game } from "./pkg/uno.js";
+
+const $ = (id) => document.getElementById(id);
+
+const COLORS = { b: "blue", g: "green", y: "yellow", r: "red" };
+
+// The page always plays seat 0.
+const SEAT = 0;
+
+let game = null;
+let seenEvents = 0;
+
+// Cards come in the engine notation: `r5`, `w` and `w4`.
+function cardElement(card) {
+  const element = document.createElement("span");
+  const wild = card.startsWith("w");
+  element.className = `card ${wild ? "" : COLORS[card[0]]}`;
+  element.textContent = wild ? (card === "w4" ? "+4" : "wild") : card.slice(1);
+  return element;
+}
+
+function notation(card) {
+  if (card.special) {
+    return card.special.kind === "draw_four" ? "w4" : "w";
+  }
+  const numerals = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+  return card.normal.color[0] + numerals.indexOf(card.normal.kind.numeric);
+}
+
+function actionLabel(action) {
+  if (action === "bluff") return "Call bluff";
+  if (action === "pass") return "Pass";
+  if (action.startsWith("draw")) return `Draw ${action.slice(4)}`;
+  if (Object.values(COLORS).includes(action)) return `Choose ${action}`;
+  return `Play ${action}`;
+}
+
+function log() {
+  for (const event of game.events(seenEvents)) {
+    const item = document.createElement("li");
+    item.textContent = event;
+    $("log").append(item);
+    seenEvents++;
+  }
+  $("log").scrollTop = $("log").scrollHeight;
+}
+
+function show() {
+  log();
+  const view = JSON.parse(game.view(SEAT));
+
+  $("table-info").textContent =
+    `Drawing deck: ${view.drawing_deck_len}` +
+    ` · hands: ${view.hand_sizes.map((size, idx) => `seat ${idx + 1} ${size}`).join(", ")}` +
+    (view.active_color ? ` · color: ${view.active_color}` : "");
+  $("top-card").replaceChildren(cardElement(notation(view.top_card)));
+  $("hand").replaceChildren(...view.hand.map((card) => cardElement(notation(card))));
+
+  if (game.winner !== undefined) {
+    $("status").textContent = game.winner === SEAT ? "You won!" : `Seat ${game.winner + 1} won`;
+    $("actions").replaceChildren();
+    return;
+  }
+
+  $("status").textContent = "Your turn";
+  $("actions").replaceChildren(...game.legalActions().map((action) => {
+    const button = document.createElement("button");
+    button.textContent = actionLabel(action);
+    button.onclick = () => {
+      game.applyAction(action);
+      game.playBots();
+      show();
+    };
+    return button;
+  }));
+}
+
+$("new-game").onsubmit = (event) => {
+  event.preventDefault();
+  try {
+    game = new Game(Number($("seats").value), $("ruleset").value, Date.now() >>> 0);
+    for (let seat = 0; seat < game.numPlayers; seat++) {
+      if (seat !== SEAT) {
+        game.setBot(seat, $("bot").value);
+      }
+    }
+  } catch (message) {
+    $("status").textContent = message;
+    return;
+  }
+
+  seenEvents = 0;
+  $("log").replaceChildren();
+  game.playBots();
+  show();
+};
+
+await init();
+$("status").textContent = "ready";